@@ -6,7 +6,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use lazy_static::lazy_static;
 use log::info;
@@ -14,7 +18,72 @@ use regex::Regex;
 use serde::Serialize;
 use tinytemplate::TinyTemplate;
 
-use crate::CVResult;
+use crate::{CVResult, Opt, Status};
+
+/// How much longer than `--timeout` we give a backend before killing it.
+/// (This gives backends that enforce the timeout themselves time to shut down
+/// cleanly.)
+const TIMEOUT_GRACE: Duration = Duration::from_secs(10);
+
+/// How long to let a backend run before killing it (if there is a limit).
+pub fn watchdog_timeout(opt: &Opt) -> Option<Duration> {
+    opt.timeout
+        .map(|secs| Duration::from_secs(secs) + TIMEOUT_GRACE)
+}
+
+/// Explain a timeout in terms of the limit set by the user (not including
+/// `TIMEOUT_GRACE`).
+pub fn timeout_message(opt: &Opt) -> String {
+    match opt.timeout {
+        Some(secs) => format!("time limit of {}s reached (--timeout)", secs),
+        None => "time limit reached".to_string(),
+    }
+}
+
+/// What a backend found out while verifying a single entry point.
+#[derive(Debug)]
+pub struct Outcome {
+    pub status:      Status,
+    /// The backend output that explains a failure (e.g. a panic message).
+    pub message:     Option<String>,
+    /// Statistics reported by the backend (e.g. KLEE's "completed paths").
+    pub stats:       HashMap<String, isize>,
+    /// Concrete test inputs generated by the backend.
+    pub ktest_files: Vec<PathBuf>,
+    /// Error descriptions generated by the backend.
+    pub err_files:   Vec<PathBuf>,
+    /// The cover points that were reached (see `verifier::cover!`) and
+    /// whether their condition was true on some path.
+    pub cover:       BTreeMap<String, bool>,
+}
+
+impl Outcome {
+    /// An outcome with no statistics or output files.
+    pub fn new(status: Status) -> Self {
+        Outcome {
+            status,
+            message: None,
+            stats: HashMap::new(),
+            ktest_files: Vec::new(),
+            err_files: Vec::new(),
+            cover: BTreeMap::new(),
+        }
+    }
+}
+
+/// Find the cover points (see `verifier::cover!`) in the output of a test.
+/// Returns whether the condition of each cover point was true on some path.
+pub fn cover_points(output: &str) -> BTreeMap<String, bool> {
+    let mut cover = BTreeMap::new();
+    for l in output.lines() {
+        if let Some(label) = l.strip_prefix("VERIFIER_COVER: reached ") {
+            cover.entry(label.to_string()).or_insert(false);
+        } else if let Some(label) = l.strip_prefix("VERIFIER_COVER: hit ") {
+            cover.insert(label.to_string(), true);
+        }
+    }
+    cover
+}
 
 /// Detect lines that match #[should_panic(expected = ...)] string.
 pub fn is_expected_panic(line: &str, expect: &Option<&str>, name: &str) -> bool {
@@ -57,6 +126,21 @@ struct FormatFlagContext<'a> {
     output_dir: &'a str,
 }
 
+/// Classify the failure of a test based on its panic message.
+pub fn panic_status(message: &str) -> Status {
+    if message.contains("with overflow") {
+        Status::Overflow
+    } else if message.contains("index out of bounds") {
+        Status::OutOfBounds
+    } else if message.contains("assertion failed") {
+        Status::AssertFailed
+    } else if message.contains("panicked at") {
+        Status::Panic
+    } else {
+        Status::Error
+    }
+}
+
 /// Format a user provided backend argument by replacing Handlebars with the appropriate values.
 pub fn format_flag(flag: &str, entry: &str, bcfile: &Path, out_dir: &Path) -> CVResult<String> {
     let mut template = TinyTemplate::new();
@@ -0,0 +1,186 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+////////////////////////////////////////////////////////////////////////////////
+// Cache of verification results (--cache)
+//
+// Results are stored in the target directory, one JSON file per result.
+// The file name is a hash of everything that can affect the result of
+// verifying an entry point:
+// - the LLVM IR reachable from the entry point (or the entire bitcode file
+//   if we fail to extract it),
+// - the backend, its version and the flags we pass to it,
+// - the arguments of the program (and symbolic arguments/environment).
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{backends_common::Outcome, *};
+
+/// Bump this when the way results are computed changes.
+const CACHE_VERSION: &str = "2";
+
+/// The part of an `Outcome` that is stored in the cache.
+#[derive(Serialize, Deserialize)]
+struct CachedOutcome {
+    status:      Status,
+    message:     Option<String>,
+    stats:       HashMap<String, isize>,
+    #[serde(default)]
+    cover:       BTreeMap<String, bool>,
+    #[serde(default)]
+    ktest_files: Vec<PathBuf>,
+    #[serde(default)]
+    err_files:   Vec<PathBuf>,
+}
+
+pub struct Cache {
+    dir:    PathBuf,
+    /// Hash of everything in the key that is the same for all the entry
+    /// points.
+    common: Sha256,
+}
+
+impl Cache {
+    pub fn new(opt: &Opt) -> CVResult<Cache> {
+        let dir = get_meta_target_directory(opt)?.append("verify-cache");
+        fs::create_dir_all(&dir)?;
+
+        let mut common = Sha256::new();
+        common.update(CACHE_VERSION);
+        common.update(env!("CARGO_PKG_VERSION"));
+        common.update(backend_version(opt)?);
+        // The `Debug` output of the options is a convenient, unambiguous
+        // encoding of a list of strings.
+        common.update(format!(
+            "{:?}",
+            (
+                opt.backend.to_string(),
+                &opt.backend_flags,
+                opt.replace_backend_flags,
+                opt.timeout,
+                opt.max_memory,
+                &opt.args,
+                &opt.sym_args,
+                &opt.sym_env,
+                opt.sym_stdin,
+                opt.coverage,
+                &opt.llvm_version,
+            )
+        ));
+
+        Ok(Cache { dir, common })
+    }
+
+    /// The file that holds the result for `entry` in `bcfile`.
+    fn path(&self, opt: &Opt, bcfile: &Path, entry: &str) -> CVResult<PathBuf> {
+        let mut hasher = self.common.clone();
+        hasher.update(entry);
+        match entry_ir(opt, bcfile, entry) {
+            Ok(ir) => hasher.update(ir),
+            Err(e) => {
+                warn!(
+                    "Cannot extract '{}' ({}), using the whole bitcode file",
+                    entry, e
+                );
+                hasher.update(fs::read(bcfile)?);
+            }
+        }
+        Ok(self
+            .dir
+            .clone()
+            .append(format!("{:x}.json", hasher.finalize())))
+    }
+
+    /// Look up the previous result of verifying `entry` in `bcfile`.
+    /// Returns the result and the file that holds it (which can be passed
+    /// to `store` if there was no previous result).
+    pub fn lookup(
+        &self,
+        opt: &Opt,
+        bcfile: &Path,
+        entry: &str,
+    ) -> CVResult<(Option<Outcome>, PathBuf)> {
+        let path = self.path(opt, bcfile, entry)?;
+        let outcome = fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<CachedOutcome>(&s).ok())
+            .map(|cached| Outcome {
+                message: cached.message,
+                stats: cached.stats,
+                cover: cached.cover,
+                // The files are only useful if they have not been deleted
+                // since.
+                ktest_files: cached
+                    .ktest_files
+                    .into_iter()
+                    .filter(|f| f.exists())
+                    .collect(),
+                err_files: cached
+                    .err_files
+                    .into_iter()
+                    .filter(|f| f.exists())
+                    .collect(),
+                ..Outcome::new(cached.status)
+            });
+        Ok((outcome, path))
+    }
+
+    /// Record the result of a test.
+    pub fn store(&self, path: &Path, outcome: &Outcome) -> CVResult<()> {
+        // Failing to run the backend (or running out of time or memory) is
+        // not a property of the code.
+        if outcome.status == Status::Unknown || outcome.status == Status::Timeout {
+            return Ok(());
+        }
+        let cached = CachedOutcome {
+            status:      outcome.status,
+            message:     outcome.message.clone(),
+            stats:       outcome.stats.clone(),
+            cover:       outcome.cover.clone(),
+            ktest_files: outcome.ktest_files.clone(),
+            err_files:   outcome.err_files.clone(),
+        };
+        fs::write(path, serde_json::to_string(&cached)?)?;
+        Ok(())
+    }
+}
+
+/// The version of the backend (so that upgrading it invalidates the cache).
+fn backend_version(opt: &Opt) -> CVResult<String> {
+    let program = match opt.backend {
+        Backend::Klee => "klee",
+        Backend::Seahorn => "sea",
+        Backend::Proptest | Backend::Crux => unreachable!(),
+    };
+    let (stdout, stderr) = Command::new(program)
+        .arg("--version")
+        .output_info(opt, Verbosity::Major)?;
+    Ok(stdout + &stderr)
+}
+
+/// The LLVM IR of `entry` and everything it (transitively) refers to.
+fn entry_ir(opt: &Opt, bcfile: &Path, entry: &str) -> CVResult<String> {
+    let (stdout, _) = Command::new(format!("llvm-extract-{}", opt.llvm_version))
+        .arg("--recursive")
+        .arg(format!("--func={}", entry))
+        .arg("-S")
+        .arg("-o")
+        .arg("-")
+        .arg(bcfile)
+        .latin1_output_info(opt, Verbosity::Trivial)?;
+    Ok(stdout)
+}
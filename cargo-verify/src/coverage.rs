@@ -0,0 +1,270 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+////////////////////////////////////////////////////////////////////////////////
+// Coverage reports (--coverage)
+//
+// KLEE writes statistics about each LLVM instruction to run.istats (see
+// klee/lib/Core/StatsTracker.cpp) in callgrind format:
+//
+//   events: Icov Forks ... I ...     -- the statistics on each line
+//   fl=FILE                          -- the source file of the following lines
+//   fn=FUNCTION                      -- the function of the following lines
+//   ASMLINE LINE STAT*               -- one line per instruction
+//   cfl=FILE cfn=FUNCTION calls=...  -- followed by the (inclusive) statistics
+//                                       of a call
+//
+// The source lines come from the debug info so we can map the instructions
+// that KLEE executed back to Rust source lines and write an lcov tracefile.
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path};
+
+use crate::CVResult;
+
+/// The coverage of a function.
+#[derive(Debug)]
+pub struct Function {
+    /// The (mangled) name of the function.
+    pub name:    String,
+    pub file:    String,
+    /// The first line of the function.
+    pub line:    usize,
+    /// The number of times that the function was called.
+    pub count:   u64,
+    /// The number of source lines (in `file`) that the function contains.
+    pub lines:   usize,
+    /// The number of those lines that were executed.
+    pub covered: usize,
+}
+
+/// The coverage of a program.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    pub functions: Vec<Function>,
+    /// The execution count of each line of each file.
+    pub files:     BTreeMap<String, BTreeMap<usize, u64>>,
+}
+
+/// Read KLEE's run.istats file `path`.
+pub fn read_istats(path: &Path) -> CVResult<Coverage> {
+    parse_istats(&fs::read_to_string(path)?)
+        .map_err(|e| format!("{}: {}", path.to_string_lossy(), e).into())
+}
+
+/// Parse the contents of KLEE's run.istats file.
+pub fn parse_istats(istats: &str) -> CVResult<Coverage> {
+    let mut coverage = Coverage::default();
+
+    // The index of the statistic that counts how often an instruction was
+    // executed.
+    let mut count_index = None;
+    let mut file = String::new();
+    let mut function: Option<Function> = None;
+    // The execution count of each (file, line) of the current function.
+    let mut lines = BTreeMap::new();
+    // The statistics after a 'calls=' line belong to the call, not to an
+    // instruction.
+    let mut skip_next = false;
+
+    for line in istats.lines() {
+        if let Some(events) = line.strip_prefix("events:") {
+            let events: Vec<_> = events.split_whitespace().collect();
+            // "I" is the number of executed instructions and "Icov" only
+            // tells whether an instruction was covered.
+            count_index = events
+                .iter()
+                .position(|e| *e == "I")
+                .or_else(|| events.iter().position(|e| *e == "Icov"));
+        } else if let Some(f) = line.strip_prefix("fl=") {
+            file = f.to_string();
+        } else if let Some(name) = line.strip_prefix("fn=") {
+            if let Some(f) = function.take() {
+                coverage.add(f, std::mem::take(&mut lines));
+            }
+            let f = Function {
+                name:    name.to_string(),
+                file:    file.clone(),
+                line:    0,
+                count:   0,
+                lines:   0,
+                covered: 0,
+            };
+            function = Some(f);
+        } else if line.starts_with("calls=") {
+            skip_next = true;
+        } else if line.starts_with(|c: char| c.is_ascii_digit()) {
+            if skip_next {
+                skip_next = false;
+                continue;
+            }
+            let index = count_index.ok_or("no 'I' or 'Icov' statistic")?;
+            let f = function.as_mut().ok_or("statistics outside a function")?;
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let src_line: usize = fields.get(1).ok_or("missing line number")?.parse()?;
+            let count: u64 = fields.get(2 + index).ok_or("missing statistic")?.parse()?;
+            if src_line == 0 || file.is_empty() {
+                // no debug info
+                continue;
+            }
+            if f.line == 0 && file == f.file {
+                f.line = src_line;
+                f.count = count;
+            }
+            let c = lines.entry((file.clone(), src_line)).or_insert(0);
+            *c = (*c).max(count);
+        }
+    }
+    if let Some(f) = function.take() {
+        coverage.add(f, lines);
+    }
+
+    Ok(coverage)
+}
+
+impl Coverage {
+    fn add(&mut self, mut function: Function, lines: BTreeMap<(String, usize), u64>) {
+        if lines.is_empty() {
+            return;
+        }
+        // Lines in other files come from inlined functions.
+        let own_lines: Vec<u64> = lines
+            .iter()
+            .filter(|((file, _), _)| *file == function.file)
+            .map(|(_, c)| *c)
+            .collect();
+        function.lines = own_lines.len();
+        function.covered = own_lines.iter().filter(|c| **c > 0).count();
+        for ((file, line), count) in lines {
+            let c = self.files.entry(file).or_default().entry(line).or_insert(0);
+            *c = (*c).max(count);
+        }
+        self.functions.push(function);
+    }
+
+    /// Only keep the files for which `keep` returns true (and the functions
+    /// in those files).
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.files.retain(|file, _| keep(file));
+        self.functions.retain(|f| keep(&f.file));
+    }
+
+    /// Write the coverage as an lcov tracefile (see `man geninfo`) for test
+    /// `name`.
+    pub fn write_lcov(&self, name: &str, path: &Path) -> CVResult<()> {
+        let mut out = String::new();
+        for (file, lines) in &self.files {
+            writeln!(out, "TN:{}", name)?;
+            writeln!(out, "SF:{}", file)?;
+            let functions: Vec<_> = self.functions.iter().filter(|f| &f.file == file).collect();
+            for f in &functions {
+                writeln!(out, "FN:{},{}", f.line, f.name)?;
+            }
+            for f in &functions {
+                writeln!(out, "FNDA:{},{}", f.count, f.name)?;
+            }
+            writeln!(out, "FNF:{}", functions.len())?;
+            writeln!(
+                out,
+                "FNH:{}",
+                functions.iter().filter(|f| f.count > 0).count()
+            )?;
+            for (line, count) in lines {
+                writeln!(out, "DA:{},{}", line, count)?;
+            }
+            writeln!(out, "LF:{}", lines.len())?;
+            writeln!(out, "LH:{}", lines.values().filter(|c| **c > 0).count())?;
+            writeln!(out, "end_of_record")?;
+        }
+        fs::write(path, out)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISTATS: &str = "\
+version: 1
+creator: klee
+positions: instr line
+events: Icov Forks I
+ob=main.bc
+fl=src/main.rs
+fn=main
+1 3 1 0 1
+2 4 1 1 1
+3 0 1 0 1
+cfl=src/lib.rs
+cfn=helper
+calls=1 10
+4 5 1 0 7
+5 6 0 0 0
+fn=unused
+6 10 0 0 0
+7 11 0 0 0
+fl=src/lib.rs
+fn=helper
+8 2 1 0 1
+";
+
+    #[test]
+    fn parse() {
+        let coverage = parse_istats(ISTATS).unwrap();
+        let functions: Vec<_> = coverage
+            .functions
+            .iter()
+            .map(|f| {
+                (
+                    &f.name[..],
+                    &f.file[..],
+                    f.line,
+                    f.count,
+                    f.lines,
+                    f.covered,
+                )
+            })
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                ("main", "src/main.rs", 3, 1, 3, 2),
+                ("unused", "src/main.rs", 10, 0, 2, 0),
+                ("helper", "src/lib.rs", 2, 1, 1, 1),
+            ]
+        );
+        // Line 0 (no debug info) and the statistics of the call are ignored.
+        let lines: Vec<_> = coverage.files["src/main.rs"].iter().collect();
+        assert_eq!(
+            lines,
+            vec![(&3, &1), (&4, &1), (&6, &0), (&10, &0), (&11, &0)]
+        );
+    }
+
+    #[test]
+    fn missing_events() {
+        assert!(parse_istats("fl=src/main.rs\nfn=main\n1 3 1 0 1\n").is_err());
+    }
+
+    #[test]
+    fn lcov() {
+        let mut coverage = parse_istats(ISTATS).unwrap();
+        coverage.retain(|file| file == "src/main.rs");
+        let path = std::env::temp_dir().join(format!("cargo-verify-{}.lcov", std::process::id()));
+        coverage.write_lcov("test", &path).unwrap();
+        let lcov = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            lcov,
+            "TN:test\nSF:src/main.rs\n\
+             FN:3,main\nFN:10,unused\nFNDA:1,main\nFNDA:0,unused\nFNF:2\nFNH:1\n\
+             DA:3,1\nDA:4,1\nDA:6,0\nDA:10,0\nDA:11,0\nLF:5\nLH:2\nend_of_record\n"
+        );
+    }
+}
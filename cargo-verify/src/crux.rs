@@ -0,0 +1,159 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use log::warn;
+
+use crate::*;
+
+pub fn check_install() -> bool {
+    // `cargo crux-test` is provided by the `cargo-crux-test` binary that is
+    // installed together with crux-mir.
+    let output = Command::new("which").arg("cargo-crux-test").output().ok();
+
+    match output {
+        Some(output) => output.status.success(),
+        None => false,
+    }
+}
+
+/// Run cargo crux-test
+///
+/// Each `--test` filter is passed to a separate run of `cargo crux-test` so
+/// that crux only runs the selected tests and `--timeout` applies to each of
+/// them. (Without `--test`, crux runs all the tests in a single process and
+/// `--timeout` applies to the whole run.)
+pub fn run(opt: &Opt, package: &str) -> CVResult<Vec<TestResult>> {
+    let mut results: Vec<TestResult> = vec![];
+    if opt.test.is_empty() {
+        results = run_filter(opt, package, None)?;
+    } else {
+        for filter in &opt.test {
+            for r in run_filter(opt, package, Some(filter))? {
+                // A test can match more than one filter.
+                if !results.iter().any(|s| s.entry == r.entry) {
+                    results.push(r);
+                }
+            }
+        }
+    }
+
+    if results.is_empty() {
+        warn!("No tests found");
+    }
+
+    for r in &results {
+        print_result(opt, &r.name, r.status);
+    }
+
+    Ok(results)
+}
+
+/// Run cargo crux-test on the tests whose name contains `filter` (or on all
+/// tests).
+fn run_filter(opt: &Opt, package: &str, filter: Option<&str>) -> CVResult<Vec<TestResult>> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("crux-test")
+        .arg("--manifest-path")
+        .arg(&opt.cargo_toml);
+
+    if !opt.features.is_empty() {
+        cmd.arg("--features").arg(opt.features.join(","));
+    }
+
+    cmd.args(&opt.backend_flags);
+
+    // Like `cargo test FILTER`
+    if let Some(filter) = filter {
+        cmd.arg(filter);
+    }
+
+    let results = match cmd.output_info_timeout(
+        opt,
+        Verbosity::Major,
+        backends_common::watchdog_timeout(opt),
+    ) {
+        Err(e) => {
+            let (status, message) = if e.is::<Timeout>() {
+                (Status::Timeout, backends_common::timeout_message(opt))
+            } else {
+                (Status::Unknown, e.to_string())
+            };
+            error!("{}", message);
+            // We don't know which tests crux ran so we report a single result.
+            let name = filter.unwrap_or(package);
+            vec![TestResult {
+                message: Some(message),
+                ..TestResult::new(opt, package, name, status)
+            }]
+        }
+        Ok((stdout, _, success)) => {
+            let results = test_results(opt, package, &stdout);
+            if results.is_empty() && !success {
+                Err("FAILED: cargo crux-test failed")?
+            }
+            results
+        }
+    };
+
+    Ok(results)
+}
+
+/// Extract the result of each test from the output of `cargo crux-test`.
+fn test_results(opt: &Opt, package: &str, stdout: &str) -> Vec<TestResult> {
+    lazy_static! {
+        // test [CRATE/HASH::]NAME[[N]]: RESULT
+        static ref RESULT: Regex =
+            Regex::new(r"^test ((?:[^/\s]+/[0-9a-z]+::)?(\S+?)(?:\[\d+\])?): (.*)$").unwrap();
+    }
+
+    stdout
+        .lines()
+        .filter_map(|l| RESULT.captures(l))
+        .map(|caps| {
+            let raw_name = caps.get(1).unwrap().as_str();
+            let name = caps.get(2).unwrap().as_str();
+            let result = caps.get(3).unwrap().as_str();
+            let (status, message) = if result.ends_with("ok") {
+                (Status::Verified, None)
+            } else if result == "FAILED" {
+                let message = failure_message(stdout, raw_name);
+                (
+                    backends_common::panic_status(message.as_deref().unwrap_or_default()),
+                    message,
+                )
+            } else {
+                (Status::Unknown, Some(result.to_string()))
+            };
+
+            TestResult {
+                entry: raw_name.to_string(),
+                message,
+                ..TestResult::new(opt, package, name, status)
+            }
+        })
+        .collect()
+}
+
+/// Find the counterexample that crux reported for the failing test `name`.
+fn failure_message(stdout: &str, name: &str) -> Option<String> {
+    let header = format!("---- {} counterexamples ----", name);
+    let message = stdout
+        .lines()
+        .skip_while(|l| *l != header)
+        .skip(1)
+        .take_while(|l| !l.starts_with("---- ") && !l.starts_with("[Crux] Overall status"))
+        .map(|l| l.trim_start_matches("[Crux]").trim())
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if message.is_empty() {
+        None
+    } else {
+        Some(message)
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+////////////////////////////////////////////////////////////////////////////////
+// Verification harnesses for library functions (--entry)
+//
+// To verify a function in a library crate we generate a small binary crate
+// (the harness) that depends on the library and whose main function calls the
+// function with abstract values for all of its arguments
+// (using `verifier::call_abstract`).
+// The harness is then verified like any other binary crate.
+////////////////////////////////////////////////////////////////////////////////
+
+use std::fs;
+
+use crate::*;
+
+/// The name of the generated harness package.
+pub const HARNESS_PACKAGE: &str = "rvt_harness";
+
+/// Generate a harness crate that calls `entry` (a path to a function in the
+/// library crate of `opt.cargo_toml`).
+/// Returns the path to the Cargo.toml of the harness.
+pub fn generate(opt: &Opt, entry: &str) -> CVResult<PathBuf> {
+    let metadata = MetadataCommand::new()
+        .manifest_path(&opt.cargo_toml)
+        .exec()?;
+    let package = metadata.root_package().ok_or("no root package")?;
+    let lib = package
+        .targets
+        .iter()
+        .find(|t| t.kind.iter().any(|k| k == "lib" || k == "rlib"))
+        .ok_or(format!(
+            "package '{}' does not have a library",
+            package.name
+        ))?;
+    // The name used to refer to the library in Rust code.
+    let lib_name = lib.name.replace('-', "_");
+
+    // Allow the path to start with, or without, the name of the crate.
+    let entry = if entry.split("::").next() == Some(&lib_name) {
+        entry.to_string()
+    } else {
+        format!("{}::{}", lib_name, entry)
+    };
+
+    let rvt_dir = PathBuf::from(std::env::var("RVT_DIR")?);
+    let annotations = rvt_dir.append("verification-annotations");
+
+    // Features of the harness: the verifier features are passed on to
+    // verification-annotations and (if it has them) to the library, the other
+    // features are passed on to the library.
+    let mut features = String::new();
+    for f in &opt.features {
+        let mut deps = vec![];
+        if f.starts_with("verifier-") {
+            deps.push(format!("verification-annotations/{}", f));
+        }
+        if package.features.contains_key(f) {
+            deps.push(format!("{}/{}", package.name, f));
+        }
+        features.push_str(&format!("{} = {:?}\n", f, deps));
+    }
+
+    let manifest = format!(
+        r#"# Generated by cargo-verify, do not edit.
+[package]
+name = "{harness}"
+version = "0.0.0"
+edition = "2018"
+publish = false
+
+# Do not become part of the workspace of the library.
+[workspace]
+
+[dependencies]
+{package} = {{ path = {package_dir:?} }}
+verification-annotations = {{ path = {annotations:?} }}
+
+[features]
+{features}"#,
+        harness = HARNESS_PACKAGE,
+        package = package.name,
+        package_dir = package
+            .manifest_path
+            .parent()
+            .ok_or("no package directory")?,
+        annotations = annotations,
+        features = features,
+    );
+
+    let main = format!(
+        r#"// Generated by cargo-verify, do not edit.
+use verification_annotations::verifier;
+
+fn main() {{
+    verifier::call_abstract({});
+}}
+"#,
+        entry
+    );
+
+    // {target_dir}/verify-harness/{entry}/
+    let dir = metadata
+        .target_directory
+        .append("verify-harness")
+        .append(entry.replace("::", "-"));
+    fs::create_dir_all(dir.clone().append("src"))?;
+
+    // Only write the files if they changed, to avoid unnecessary rebuilds.
+    let write = |path: PathBuf, contents: &str| -> CVResult<PathBuf> {
+        if fs::read_to_string(&path).ok().as_deref() != Some(contents) {
+            fs::write(&path, contents)?;
+        }
+        Ok(path)
+    };
+    write(dir.clone().append("src").append("main.rs"), &main)?;
+    let cargo_toml = write(dir.append("Cargo.toml"), &manifest)?;
+
+    info_at!(
+        &opt,
+        Verbosity::Informative,
+        "  Generated harness {} for {}",
+        cargo_toml.to_string_lossy(),
+        entry
+    );
+
+    Ok(cargo_toml)
+}
@@ -8,11 +8,12 @@
 
 use std::{collections::HashMap, ffi::OsString, fs, path::Path, process::Command};
 
+use cargo_metadata::Message;
 use lazy_static::lazy_static;
 use log::{info, warn};
 use regex::Regex;
 
-use crate::{utils::Append, *};
+use crate::{backends_common::Outcome, utils::Append, *};
 
 /// Check if Klee is avilable.
 pub fn check_install() -> bool {
@@ -25,7 +26,7 @@ pub fn check_install() -> bool {
 }
 
 /// Run Klee and replay
-pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Status> {
+pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Outcome> {
     // KLEE output files are put in kleeout directory with filename `name`
     let klee_dir = opt.cargo_toml.with_file_name("kleeout");
     fs::create_dir_all(&klee_dir)?;
@@ -46,13 +47,14 @@ pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Sta
     info!("      entry: {}", entry);
     info!("      results: {}", out_dir.to_string_lossy());
 
-    let (status, stats) = run(&opt, &name, &entry, &bcfile, &out_dir)?;
-    if !stats.is_empty() {
-        match stats.get("completed paths") {
+    let outcome = run(&opt, &name, &entry, &bcfile, &out_dir)?;
+    let status = outcome.status;
+    if !outcome.stats.is_empty() {
+        match outcome.stats.get("completed paths") {
             Some(n) => info!("     {}: {} paths", name, n),
             None => (),
         }
-        info!("     {}: {:?}", name, stats);
+        info!("     {}: {:?}", name, outcome.stats);
     }
 
     // {out_dir}/test*.err
@@ -63,38 +65,128 @@ pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Sta
     failures.sort_unstable();
     info!("      Failing test: {:?}", failures);
 
+    // {out_dir}/test*.ktest
+    let mut all_ktests =
+        glob(&glob::Pattern::escape(out_dir.to_str().ok_or("not UTF-8")?).append("/test*.ktest"))?
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+    all_ktests.sort_unstable();
+
+    // Remove the '.err' extension and replace the '.*' ('.abort' or
+    // '.ptr') with '.ktest'.
+    let mut failing_ktests = failures
+        .iter()
+        .map(|p| {
+            p.with_extension("") // Remove '.err'
+                .with_extension("ktest") // Replace '.*' with '.ktest'
+        })
+        .collect::<Vec<_>>();
+    failing_ktests.sort_unstable();
+    failing_ktests.dedup();
+
+    if opt.coverage {
+        if let Err(err) = report_coverage(opt, name, &out_dir) {
+            warn!("Failed to report coverage: {}", err);
+        }
+    }
+
+    // The output of replaying each input (so that we replay it only once).
+    let mut replays = HashMap::new();
+
     if opt.replay > 0 {
         // use -r -r to see all tests, not just failing tests
-        let mut ktests = if opt.replay > 1 {
-            // {out_dir}/test*.ktest
-            glob(
-                &glob::Pattern::escape(out_dir.to_str().ok_or("not UTF-8")?).append("/test*.ktest"),
-            )?
-            .filter_map(Result::ok)
-            .collect::<Vec<_>>()
+        let ktests = if opt.replay > 1 {
+            &all_ktests
         } else {
-            // Remove the '.err' extension and replace the '.*' ('.abort' or
-            // '.ptr') with '.ktest'.
-            failures
-                .iter()
-                .map(|p| {
-                    p.with_extension("") // Remove '.err'
-                        .with_extension("ktest") // Replace '.*' with '.ktest'
-                })
-                .collect::<Vec<_>>()
+            &failing_ktests
         };
-        ktests.sort_unstable();
 
         for ktest in ktests {
             println!("    Test input {}", ktest.to_str().unwrap_or("???"));
-            match replay_klee(&opt, &name, &ktest) {
-                Ok(()) => (),
+
+            // Show the input values (even if the program cannot be replayed).
+            match ktest::read(ktest) {
+                Ok(ktest) => {
+                    #[rustfmt::skip]
+                    info_at!(&opt, Verbosity::Minor, "      ktest version {}, args {:?}, sym_argvs {} (length {})",
+                             ktest.version, ktest.args, ktest.sym_argvs, ktest.sym_argv_len);
+                    for object in &ktest.objects {
+                        println!("      {}", object);
+                    }
+                }
+                Err(err) => warn!("Failed to read test input: {}", err),
+            }
+
+            match replay_klee(&opt, &name, ktest) {
+                Ok(output) => {
+                    for line in output.lines() {
+                        println!("{}", line);
+                    }
+                    replays.insert(ktest.clone(), output);
+                }
                 Err(err) => warn!("Failed to replay: {}", err),
             }
         }
     }
 
-    Ok(status)
+    if let Some(dir) = &opt.regression_tests {
+        for ktest in &failing_ktests {
+            let output = match replays.remove(ktest) {
+                Some(output) => Ok(output),
+                None => replay_klee(opt, name, ktest),
+            };
+            match output
+                .and_then(|output| regression::generate(opt, dir, name, status, ktest, &output))
+            {
+                Ok(Some(path)) => info_at!(
+                    &opt,
+                    Verbosity::Normal,
+                    "    Wrote regression test {}",
+                    path.to_string_lossy()
+                ),
+                Ok(None) => (),
+                Err(err) => warn!("Failed to generate a regression test: {}", err),
+            }
+        }
+    }
+
+    Ok(Outcome {
+        ktest_files: all_ktests,
+        err_files: failures,
+        ..outcome
+    })
+}
+
+/// Write an lcov file (in `out_dir`) with the lines of the crate that KLEE
+/// executed and print the coverage of each function.
+fn report_coverage(opt: &Opt, name: &str, out_dir: &Path) -> CVResult<()> {
+    let mut coverage = coverage::read_istats(&out_dir.join("run.istats"))?;
+
+    // Only report the source files of the crate (or workspace), not those of
+    // the standard library or of dependencies.
+    let root = fs::canonicalize(&opt.cargo_toml)?
+        .parent()
+        .ok_or("no crate directory")?
+        .to_path_buf();
+    coverage.retain(|file| Path::new(file).starts_with(&root));
+
+    let lcov = out_dir.join("coverage.info");
+    coverage.write_lcov(name, &lcov)?;
+    println!("    Coverage {}", lcov.to_string_lossy());
+    for f in &coverage.functions {
+        let file = Path::new(&f.file)
+            .strip_prefix(&root)
+            .unwrap_or_else(|_| Path::new(&f.file));
+        println!(
+            "      {:>5}/{:<5} lines  {:#}  ({}:{})",
+            f.covered,
+            f.lines,
+            rustc_demangle::demangle(&f.name),
+            file.to_string_lossy(),
+            f.line
+        );
+    }
+    Ok(())
 }
 
 /// Return an int indicating importance of a line from KLEE's output
@@ -107,7 +199,10 @@ pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Sta
 /// 4: non-KLEE output
 /// 5: any other KLEE output
 fn importance(line: &str, expect: &Option<&str>, name: &str) -> i8 {
-    if line.starts_with("VERIFIER_EXPECT:") {
+    if line.starts_with("VERIFIER_EXPECT:")
+        || line.starts_with("VERIFIER_COVER:")
+        || line.starts_with("VERIFIER_ASSERT:")
+    {
         4
     } else if backends_common::is_expected_panic(&line, &expect, &name) {
         // low priority because we report it directly
@@ -154,13 +249,7 @@ fn importance(line: &str, expect: &Option<&str>, name: &str) -> i8 {
 }
 
 /// Run Klee and analyse its output.
-fn run(
-    opt: &Opt,
-    name: &str,
-    entry: &str,
-    bcfile: &Path,
-    out_dir: &Path,
-) -> CVResult<(Status, HashMap<String, isize>)> {
+fn run(opt: &Opt, name: &str, entry: &str, bcfile: &Path, out_dir: &Path) -> CVResult<Outcome> {
     let mut cmd = Command::new("klee");
 
     let user_flags: Vec<_> = opt
@@ -179,17 +268,30 @@ fn run(
             "--libc=klee",
             "--silent-klee-assume",
             "--disable-verify", // workaround https://github.com/klee/klee/issues/937
-        ])
-        .arg("--output-dir")
-        .arg(out_dir)
-        .args(user_flags)
-        .arg(bcfile)
-        .args(&opt.args);
+        ]);
+
+        if let Some(secs) = opt.timeout {
+            cmd.arg(format!("--max-time={}s", secs));
+        }
+        if let Some(mb) = opt.max_memory {
+            cmd.arg(format!("--max-memory={}", mb));
+        }
+
+        cmd.arg("--output-dir")
+            .arg(out_dir)
+            .args(user_flags)
+            .arg(bcfile)
+            .args(symbolic_args(opt))
+            .args(&opt.args);
     } else {
         cmd.args(user_flags);
     }
 
-    let (_, stderr, _) = cmd.latin1_output_info_ignore_exit(&opt, Verbosity::Major)?;
+    let (_, stderr, _) = cmd.latin1_output_info_timeout(
+        &opt,
+        Verbosity::Major,
+        backends_common::watchdog_timeout(opt),
+    )?;
 
     // We scan stderr for:
     // 1. Indications of the expected output (eg from #[should_panic])
@@ -212,13 +314,18 @@ fn run(
     }
 
     // Scan for first message that indicates result
-    let status = stderr
+    // (and keep that message to explain failures)
+    let (status, message) = stderr
         .lines()
         .find_map(|l| {
-            if l.starts_with("KLEE: HaltTimer invoked") {
+            let status = if l.starts_with("KLEE: HaltTimer invoked") {
                 Some(Status::Timeout)
             } else if l.starts_with("KLEE: halting execution, dumping remaining states") {
                 Some(Status::Timeout)
+            } else if l.contains("over memory cap") {
+                // KLEE kills states when it exceeds --max-memory so the
+                // exploration is incomplete.
+                Some(Status::Unknown)
             } else if l.starts_with("KLEE: ERROR: Could not link") {
                 Some(Status::Unknown)
             } else if l.starts_with("KLEE: ERROR: Unable to load symbol") {
@@ -253,11 +360,26 @@ fn run(
                 }
             } else {
                 None
-            }
+            };
+            status.map(|status| {
+                let message = if status == Status::Verified {
+                    None
+                } else if status == Status::Timeout {
+                    Some(backends_common::timeout_message(opt))
+                } else if l.contains("over memory cap") {
+                    Some(match opt.max_memory {
+                        Some(mb) => format!("memory limit of {}MB reached (--max-memory)", mb),
+                        None => "memory limit reached".to_string(),
+                    })
+                } else {
+                    Some(l.to_string())
+                };
+                (status, message)
+            })
         })
         .unwrap_or_else(|| {
             warn!("Unable to determine status of {}", name);
-            Status::Unknown
+            (Status::Unknown, None)
         });
 
     info!("Status: '{}' expected: '{:?}'", status, expect);
@@ -283,52 +405,204 @@ fn run(
         })
         .collect();
 
+    // A test that passes without completing any path (e.g., because its
+    // assumptions are unsatisfiable) checked nothing.
+    // Paths that fail an assumption are silently terminated: they produce no
+    // test input (and, in recent versions of KLEE, are not "completed").
+    // With '--require-assertions', tests that complete some path without
+    // reaching a `verifier::assert!` (which prints "VERIFIER_ASSERT: reached"
+    // on each path that reaches one) are also vacuous.
+    // Other assertions (e.g., std's `assert!`) cannot be detected so this
+    // check is optional.
+    let no_path =
+        stats.get("completed paths") == Some(&0) || stats.get("generated tests") == Some(&0);
+    let no_assertion =
+        opt.require_assertions && !stderr.lines().any(|l| l == "VERIFIER_ASSERT: reached");
+    let (status, message) = if status == Status::Verified && expect.is_none() && no_path {
+        let message = "no path reached the end of the test (are the assumptions satisfiable?)";
+        (Status::Vacuous, Some(message.to_string()))
+    } else if status == Status::Verified && expect.is_none() && no_assertion {
+        let message = "no path reached an assertion (--require-assertions)";
+        (Status::Vacuous, Some(message.to_string()))
+    } else {
+        (status, message)
+    };
+
     for l in stderr.lines() {
         if importance(&l, &expect, &name) < opt.verbose as i8 {
             println!("{}", l);
         }
     }
 
-    Ok((status, stats))
+    Ok(Outcome {
+        message,
+        stats,
+        cover: backends_common::cover_points(&stderr),
+        ..Outcome::new(status)
+    })
 }
 
-/// Replay a KLEE "ktest" file
-fn replay_klee(opt: &Opt, name: &str, ktest: &Path) -> CVResult<()> {
-    let mut cmd = Command::new("cargo");
-
-    if opt.tests || !opt.test.is_empty() {
-        cmd.arg("test").arg("--manifest-path").arg(&opt.cargo_toml);
+/// The arguments that ask the runtime library to create symbolic arguments,
+/// environment variables and standard input (see runtime/src/symbolic_env.c).
+fn symbolic_args(opt: &Opt) -> Vec<String> {
+    let mut args = vec![];
+    for spec in &opt.sym_env {
+        args.push("--rvt-sym-env".to_string());
+        args.push(spec.clone());
+    }
+    for n_len in opt.sym_args.chunks(2) {
+        args.push("--rvt-sym-args".to_string());
+        args.extend(n_len.iter().map(usize::to_string));
+    }
+    if let Some(n) = opt.sym_stdin {
+        args.push("--rvt-sym-stdin".to_string());
+        args.push(n.to_string());
+    }
+    args
+}
 
-        if !opt.features.is_empty() {
-            cmd.arg("--features").arg(opt.features.join(","));
+/// The values of the symbolic arguments and environment variables in `ktest`
+/// (see `symbolic_args`).
+fn symbolic_inputs(ktest: &Path) -> CVResult<(Vec<String>, HashMap<String, String>)> {
+    let mut args = vec![];
+    let mut env = HashMap::new();
+    for object in ktest::read(ktest)?.objects {
+        let value = object.c_string();
+        let name = object.short_name();
+        if name.starts_with("argv[") {
+            args.push(value);
+        } else if let Some(var) = name.strip_prefix('$') {
+            env.insert(var.to_string(), value);
         }
+    }
+    Ok((args, env))
+}
 
-        cmd.arg(&name).args(&["--", "--nocapture"]);
-    } else {
-        cmd.arg("run").arg("--manifest-path").arg(&opt.cargo_toml);
-
-        if !opt.features.is_empty() {
-            cmd.arg("--features").arg(opt.features.join(","));
+/// The contents of the symbolic standard input in `ktest` (see `symbolic_args`).
+fn symbolic_stdin(ktest: &Path) -> CVResult<Vec<u8>> {
+    for object in ktest::read(ktest)?.objects {
+        if object.short_name() == "stdin" {
+            return Ok(object.bytes);
         }
+    }
+    Ok(vec![])
+}
 
-        if !opt.args.is_empty() {
-            cmd.arg("--").args(opt.args.iter());
+/// Replay a KLEE "ktest" file
+/// Run the program (or test) on the input `ktest` and return its output.
+///
+/// The program is run with the same arguments and environment as when it was
+/// verified: the symbolic arguments come before the concrete arguments and,
+/// if there are symbolic variables, the environment only contains those
+/// variables (see runtime/src/symbolic_env.c).
+fn replay_klee(opt: &Opt, name: &str, ktest: &Path) -> CVResult<String> {
+    let is_test = opt.tests || !opt.test.is_empty();
+
+    // Build the program (or tests) with cargo and then run the executables
+    // ourselves so that we control their arguments and environment.
+    let mut cmd = Command::new("cargo");
+    if is_test {
+        cmd.arg("test").arg("--no-run");
+    } else {
+        cmd.arg("build");
+        if let Some(bin) = &opt.bin {
+            cmd.arg("--bin").arg(bin);
         }
     }
+    cmd.arg("--manifest-path")
+        .arg(&opt.cargo_toml)
+        .arg("--message-format=json");
+    if !opt.features.is_empty() {
+        cmd.arg("--features").arg(opt.features.join(","));
+    }
 
+    // If it has been built, link with the native version of the file system
+    // model used by verifier::fs (see runtime/src/fs.c).
+    // Programs that do not use verifier::fs can be replayed without it.
+    let replay_lib = PathBuf::from(std::env::var("RVT_DIR")?)
+        .append("runtime")
+        .append("librvt-replay.a");
+    let mut replay_flags = OsString::from("--cfg=verify");
+    if replay_lib.exists() {
+        replay_flags = replay_flags
+            .append(" -Clink-arg=-Wl,--wrap=open,--wrap=open64,--wrap=read,--wrap=close,--wrap=lseek64,--wrap=fstat64")
+            .append(" -Clink-arg=")
+            .append(&replay_lib);
+    } else {
+        info_at!(
+            opt,
+            Verbosity::Major,
+            "  Replaying without virtual files: {} not found",
+            replay_lib.to_string_lossy()
+        );
+    }
     let rustflags = match std::env::var_os("RUSTFLAGS") {
-        Some(env_rustflags) => env_rustflags.append(" --cfg=verify"),
-        None => OsString::from("--cfg=verify"),
+        Some(env_rustflags) => env_rustflags.append(" ").append(replay_flags),
+        None => replay_flags,
     };
-    cmd.env("RUSTFLAGS", rustflags).env("KTEST_FILE", ktest);
+    cmd.env("RUSTFLAGS", rustflags);
 
-    // Note that we do not treat this as an error, because
-    // the interesting case for replay is when KLEE had found an error.
-    let (stdout, stderr, _success) = cmd.output_info_ignore_exit(&opt, Verbosity::Major)?;
+    let (stdout, _) = cmd.output_info(opt, Verbosity::Major).map_err(|err| {
+        if replay_lib.exists() {
+            err
+        } else {
+            format!(
+                "{}\nPrograms that use verifier::fs can only be replayed after building {} (make -C runtime replay)",
+                err,
+                replay_lib.to_string_lossy()
+            )
+            .into()
+        }
+    })?;
+    let executables: Vec<PathBuf> = Message::parse_stream(stdout.as_bytes())
+        .filter_map(Result::ok)
+        .filter_map(|message| match message {
+            Message::CompilerArtifact(artifact) if artifact.profile.test == is_test => {
+                artifact.executable
+            }
+            _ => None,
+        })
+        .collect();
 
-    for line in stdout.lines().chain(stderr.lines()) {
-        println!("{}", line);
+    let (sym_args, sym_env) = if opt.sym_args.is_empty() && opt.sym_env.is_empty() {
+        (vec![], HashMap::new())
+    } else {
+        symbolic_inputs(ktest)?
+    };
+
+    let mut output = String::new();
+    for exe in &executables {
+        let mut cmd = Command::new(exe);
+        if !opt.sym_env.is_empty() {
+            cmd.env_clear().envs(&sym_env);
+        }
+        cmd.env("KTEST_FILE", ktest);
+
+        if is_test {
+            // Like cargo test, run the tests in the directory of the crate.
+            cmd.current_dir(opt.cargo_toml.parent().unwrap_or(Path::new(".")))
+                .arg(name)
+                .arg("--nocapture");
+        } else {
+            cmd.args(&sym_args).args(&opt.args);
+
+            // The symbolic standard input is piped into the program.
+            if opt.sym_stdin.is_some() {
+                let stdin_file = ktest.with_extension("stdin");
+                fs::write(&stdin_file, symbolic_stdin(ktest)?)?;
+                cmd.stdin(fs::File::open(&stdin_file)?);
+            }
+        }
+
+        // Note that we do not treat this as an error, because
+        // the interesting case for replay is when KLEE had found an error.
+        let (stdout, stderr, _success) = cmd.output_info_ignore_exit(opt, Verbosity::Major)?;
+        output.push_str(&stdout);
+        output.push_str(&stderr);
+    }
+    if executables.is_empty() {
+        Err("cargo did not build any executable to replay")?
     }
 
-    Ok(())
+    Ok(output)
 }
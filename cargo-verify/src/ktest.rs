@@ -0,0 +1,344 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+////////////////////////////////////////////////////////////////////////////////
+// Parser for KLEE's .ktest files (the inputs that KLEE generates)
+//
+// The format (see klee/lib/Basic/KTest.cpp) is, with all integers being 32-bit
+// big-endian:
+//
+//   "KTEST" version
+//   num_args (len bytes)*           -- the command line of KLEE
+//   [sym_argvs sym_argv_len]        -- only if version >= 2
+//   num_objects (len name len bytes)*
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{convert::TryInto, fmt, fs, path::Path};
+
+use crate::{utils::from_latin1, CVResult};
+
+/// The contents of a .ktest file.
+#[derive(Debug)]
+pub struct KTest {
+    pub version:      u32,
+    /// The command line that KLEE was invoked with.
+    pub args:         Vec<String>,
+    pub sym_argvs:    u32,
+    pub sym_argv_len: u32,
+    pub objects:      Vec<KTestObject>,
+}
+
+/// A symbolic object (and the concrete value that KLEE picked for it).
+#[derive(Debug)]
+pub struct KTestObject {
+    pub name:  String,
+    pub bytes: Vec<u8>,
+}
+
+/// Read and parse the .ktest file `path`.
+pub fn read(path: &Path) -> CVResult<KTest> {
+    parse(&fs::read(path)?).map_err(|e| format!("{}: {}", path.to_string_lossy(), e).into())
+}
+
+/// Parse the contents of a .ktest file.
+pub fn parse(data: &[u8]) -> CVResult<KTest> {
+    let mut reader = Reader { data, pos: 0 };
+
+    let magic = reader.take(5)?;
+    if magic != b"KTEST" && magic != b"BOUT\n" {
+        Err("not a ktest file")?
+    }
+    let version = reader.u32()?;
+    if version > 3 {
+        Err(format!("unsupported ktest version {}", version))?
+    }
+
+    let num_args = reader.u32()?;
+    let args = (0..num_args)
+        .map(|_| reader.sized().map(from_latin1))
+        .collect::<CVResult<Vec<_>>>()?;
+
+    let (sym_argvs, sym_argv_len) = if version >= 2 {
+        (reader.u32()?, reader.u32()?)
+    } else {
+        (0, 0)
+    };
+
+    let num_objects = reader.u32()?;
+    let objects = (0..num_objects)
+        .map(|_| {
+            let name = from_latin1(reader.sized()?);
+            let bytes = reader.sized()?.to_vec();
+            Ok(KTestObject { name, bytes })
+        })
+        .collect::<CVResult<Vec<_>>>()?;
+
+    Ok(KTest {
+        version,
+        args,
+        sym_argvs,
+        sym_argv_len,
+        objects,
+    })
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos:  usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> CVResult<&'a [u8]> {
+        let end = self.pos.checked_add(n).ok_or("truncated ktest file")?;
+        let r = self.data.get(self.pos..end).ok_or("truncated ktest file")?;
+        self.pos = end;
+        Ok(r)
+    }
+
+    fn u32(&mut self) -> CVResult<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    /// A length followed by that many bytes.
+    fn sized(&mut self) -> CVResult<&'a [u8]> {
+        let n = self.u32()? as usize;
+        self.take(n)
+    }
+}
+
+impl KTestObject {
+    /// The name given to the object when it was made symbolic, without the
+    /// type (see `type_name`).
+    pub fn short_name(&self) -> &str {
+        match self.name.split_once(": ") {
+            Some((name, _)) => name.trim(),
+            None => &self.name,
+        }
+    }
+
+    /// The Rust type of the object if it is part of the name ("a: u32").
+    pub fn type_name(&self) -> Option<&str> {
+        self.name.split_once(": ").map(|(_, ty)| ty.trim())
+    }
+
+    /// The value as a nul-terminated string.
+    pub fn c_string(&self) -> String {
+        let len = self
+            .bytes
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(self.bytes.len());
+        from_latin1(&self.bytes[..len])
+    }
+
+    /// The value as an integer of `N` bytes (KLEE runs on little-endian hosts).
+    fn le<const N: usize>(&self) -> Option<[u8; N]> {
+        self.bytes.as_slice().try_into().ok()
+    }
+
+    fn is_ascii_string(&self) -> bool {
+        !self.bytes.is_empty()
+            && self
+                .bytes
+                .iter()
+                .all(|b| b.is_ascii_graphic() || *b == b' ')
+    }
+
+    /// Format the value according to its type, or guess the type from the size
+    /// of the value.
+    fn format_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        macro_rules! int {
+            ($ty:ty) => {
+                if let Some(b) = self.le() {
+                    return write!(f, "{}", <$ty>::from_le_bytes(b));
+                }
+            };
+        }
+
+        match self.type_name() {
+            Some("bool") if self.bytes.len() == 1 => {
+                return match self.bytes[0] {
+                    0 => write!(f, "false"),
+                    1 => write!(f, "true"),
+                    b => write!(f, "{} (invalid bool)", b),
+                }
+            }
+            Some("char") => {
+                if let Some(c) = self.le().map(u32::from_le_bytes).and_then(char::from_u32) {
+                    return write!(f, "{:?}", c);
+                }
+            }
+            Some("u8") => int!(u8),
+            Some("u16") => int!(u16),
+            Some("u32") => int!(u32),
+            Some("u64") => int!(u64),
+            Some("u128") => int!(u128),
+            Some("usize") => int!(usize),
+            Some("i8") => int!(i8),
+            Some("i16") => int!(i16),
+            Some("i32") => int!(i32),
+            Some("i64") => int!(i64),
+            Some("i128") => int!(i128),
+            Some("isize") => int!(isize),
+            Some("f32") => {
+                if let Some(b) = self.le() {
+                    return write!(f, "{:?}", f32::from_le_bytes(b));
+                }
+            }
+            Some("f64") => {
+                if let Some(b) = self.le() {
+                    return write!(f, "{:?}", f64::from_le_bytes(b));
+                }
+            }
+            Some("String") | Some("str") if self.is_ascii_string() => {
+                return write!(f, "{:?}", from_latin1(&self.bytes));
+            }
+            Some("CString") => return write!(f, "{:?}", self.c_string()),
+            Some("Vec<u8>") => return write!(f, "{:?}", self.bytes),
+            _ => (),
+        }
+
+        // We don't know the type (or the value does not fit the type).
+        match self.bytes.len() {
+            1 => {
+                let b = self.bytes[0];
+                write!(f, "{}", b)?;
+                if (b as i8) < 0 {
+                    write!(f, " (i8: {})", b as i8)?;
+                }
+                Ok(())
+            }
+            2 => {
+                let v = u16::from_le_bytes(self.le().unwrap());
+                write!(f, "{}", v)?;
+                if (v as i16) < 0 {
+                    write!(f, " (i16: {})", v as i16)?;
+                }
+                Ok(())
+            }
+            4 => {
+                let v = u32::from_le_bytes(self.le().unwrap());
+                write!(f, "{}", v)?;
+                if (v as i32) < 0 {
+                    write!(f, " (i32: {})", v as i32)?;
+                }
+                Ok(())
+            }
+            8 => {
+                let v = u64::from_le_bytes(self.le().unwrap());
+                write!(f, "{}", v)?;
+                if (v as i64) < 0 {
+                    write!(f, " (i64: {})", v as i64)?;
+                }
+                Ok(())
+            }
+            _ if self.is_ascii_string() => write!(f, "{:?}", from_latin1(&self.bytes)),
+            _ => write!(f, "{:02x?}", self.bytes),
+        }
+    }
+}
+
+impl fmt::Display for KTestObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.short_name())?;
+        if let Some(ty) = self.type_name() {
+            write!(f, ": {}", ty)?;
+        }
+        write!(f, " = ")?;
+        self.format_value(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a ktest file.
+    fn ktest(magic: &[u8], version: u32, args: &[&str], objects: &[(&str, &[u8])]) -> Vec<u8> {
+        fn sized(out: &mut Vec<u8>, bytes: &[u8]) {
+            out.extend(&(bytes.len() as u32).to_be_bytes());
+            out.extend(bytes);
+        }
+        let mut out = magic.to_vec();
+        out.extend(&version.to_be_bytes());
+        out.extend(&(args.len() as u32).to_be_bytes());
+        for arg in args {
+            sized(&mut out, arg.as_bytes());
+        }
+        if version >= 2 {
+            out.extend(&1u32.to_be_bytes());
+            out.extend(&8u32.to_be_bytes());
+        }
+        out.extend(&(objects.len() as u32).to_be_bytes());
+        for (name, bytes) in objects {
+            sized(&mut out, name.as_bytes());
+            sized(&mut out, bytes);
+        }
+        out
+    }
+
+    #[test]
+    fn ktest_magic() {
+        let data = ktest(
+            b"KTEST",
+            3,
+            &["test.bc", "--arg"],
+            &[("a: u32", &[42, 0, 0, 0]), ("b: bool", &[1])],
+        );
+        let ktest = parse(&data).unwrap();
+        assert_eq!(ktest.version, 3);
+        assert_eq!(ktest.args, vec!["test.bc", "--arg"]);
+        assert_eq!((ktest.sym_argvs, ktest.sym_argv_len), (1, 8));
+        assert_eq!(ktest.objects.len(), 2);
+        assert_eq!(ktest.objects[0].short_name(), "a");
+        assert_eq!(ktest.objects[0].type_name(), Some("u32"));
+        assert_eq!(ktest.objects[0].to_string(), "a: u32 = 42");
+        assert_eq!(ktest.objects[1].to_string(), "b: bool = true");
+    }
+
+    #[test]
+    fn bout_magic() {
+        // Version 1 files have no sym_argvs and sym_argv_len.
+        let data = ktest(b"BOUT\n", 1, &[], &[("x", &[0xff])]);
+        let ktest = parse(&data).unwrap();
+        assert_eq!(ktest.version, 1);
+        assert_eq!((ktest.sym_argvs, ktest.sym_argv_len), (0, 0));
+        assert_eq!(ktest.objects[0].to_string(), "x = 255 (i8: -1)");
+    }
+
+    #[test]
+    fn bad_magic() {
+        let data = ktest(b"KTESX", 3, &[], &[]);
+        assert_eq!(parse(&data).unwrap_err().to_string(), "not a ktest file");
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let data = ktest(b"KTEST", 4, &[], &[]);
+        assert_eq!(
+            parse(&data).unwrap_err().to_string(),
+            "unsupported ktest version 4"
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let data = ktest(b"KTEST", 3, &["test.bc"], &[("a: u32", &[42, 0, 0, 0])]);
+        for len in 0..data.len() {
+            assert!(parse(&data[..len]).is_err(), "length {}", len);
+        }
+        // A length that does not fit in the file.
+        let mut data = ktest(b"KTEST", 3, &[], &[]);
+        data.truncate(data.len() - 4);
+        data.extend(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            parse(&data).unwrap_err().to_string(),
+            "truncated ktest file"
+        );
+    }
+}
@@ -9,14 +9,16 @@
 #![feature(command_access)]
 
 use std::{
-    collections::HashSet,
-    error, fmt,
+    collections::{BTreeMap, HashSet},
+    error,
+    ffi::OsStr,
+    fmt,
     fs::{self, File, OpenOptions},
     io::{self, Write},
     path::{Path, PathBuf},
     process::{exit, Command},
     str::from_utf8,
-    sync::Mutex,
+    sync::{Arc, Mutex},
     time::Instant,
 };
 
@@ -24,7 +26,7 @@ use ansi_term::Colour;
 use cargo_metadata::{CargoOpt, MetadataCommand};
 use glob::glob;
 use lazy_static::lazy_static;
-use log::error;
+use log::{error, warn};
 use rayon::prelude::*;
 use regex::Regex;
 use rustc_demangle::demangle;
@@ -37,15 +39,23 @@ use utils::{add_pre_ext, Append};
 mod utils;
 
 mod backends_common;
+mod cache;
+mod coverage;
+mod crux;
+mod harness;
 mod klee;
+mod ktest;
 mod proptest;
+mod regression;
+mod report;
 mod run_tools;
 mod seahorn;
 
+use report::TestResult;
 use run_tools::*;
 
 // Command line arguments
-#[derive(StructOpt)]
+#[derive(StructOpt, Clone)]
 #[structopt(
     name = "cargo-verify",
     about = "Execute verification tools",
@@ -67,6 +77,18 @@ pub struct Opt {
     #[structopt(value_name = "ARG", last = true)]
     args: Vec<String>,
 
+    /// Pass N symbolic ASCII arguments of up to MAXLEN characters to the program (before ARGs) (KLEE only)
+    #[structopt(long, number_of_values = 2, value_names = &["N", "MAXLEN"])]
+    sym_args: Vec<usize>,
+
+    /// Make environment variable VAR a symbolic ASCII string of up to MAXLEN characters (KLEE only)
+    #[structopt(long, number_of_values = 1, value_name = "VAR=MAXLEN")]
+    sym_env: Vec<String>,
+
+    /// Make standard input N symbolic bytes (KLEE only)
+    #[structopt(long, value_name = "N")]
+    sym_stdin: Option<usize>,
+
     // backend_arg is used for holding the CL option. After parsing, if the user
     // specified a backend it will be copied to the `backend` field below, if
     // the user didn't specify a backend, we will auto-detect one, and put it
@@ -93,6 +115,14 @@ pub struct Opt {
     #[structopt(long)]
     replace_backend_flags: bool,
 
+    /// Maximum time (in seconds) to spend verifying each test (reported as TIMEOUT); with --backend=crux, the limit applies to each --test filter, or to all tests if there is no filter
+    #[structopt(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Maximum memory (in MB) that the backend can use to verify each test
+    #[structopt(long, value_name = "MB")]
+    max_memory: Option<u64>,
+
     /// Specifiy the location of 'verify_c_common'
     #[structopt(long, value_name = "PATH", env = "SEAHORN_VERIFY_C_COMMON_DIR")]
     seahorn_verify_c_common_dir: Option<String>,
@@ -124,6 +154,18 @@ pub struct Opt {
     #[structopt(short, long, value_name = "PATH", parse(from_os_str))]
     output: Option<PathBuf>,
 
+    /// Reuse the results of previous runs for tests whose code, flags and backend have not changed
+    #[structopt(long)]
+    cache: bool,
+
+    /// Write a JSON report of the verification results (one record per test) to "PATH"
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    report: Option<PathBuf>,
+
+    /// Write a JUnit XML report of the verification results to "PATH"
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    junit: Option<PathBuf>,
+
     /// Verify all tests instead of 'main'
     #[structopt(short, long)]
     tests: bool,
@@ -133,6 +175,34 @@ pub struct Opt {
     #[structopt(long, number_of_values = 1, value_name = "TESTNAME")]
     test: Vec<String>,
 
+    /// Verify all packages in the workspace
+    #[structopt(long)]
+    workspace: bool,
+
+    /// Package(s) to verify
+    #[structopt(short, long, number_of_values = 1, value_name = "SPEC")]
+    package: Vec<String>,
+
+    /// Exclude packages from the verification (must be used with --workspace)
+    #[structopt(long, number_of_values = 1, value_name = "SPEC")]
+    exclude: Vec<String>,
+
+    /// Write a regression test (to include in the module of the original test) for each failing input found by KLEE to "DIR"
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    regression_tests: Option<PathBuf>,
+
+    /// Write an lcov coverage file for each test and print the coverage of each function (KLEE only)
+    #[structopt(long)]
+    coverage: bool,
+
+    /// Report tests that pass without reaching any 'verifier::assert!' as VACUOUS (KLEE only)
+    #[structopt(long)]
+    require_assertions: bool,
+
+    /// Verify the library function PATH (e.g., 'my_crate::parser::parse_header') with abstract values for all of its arguments
+    #[structopt(long, value_name = "PATH")]
+    entry: Option<String>,
+
     /// Build and run this specific binary
     #[structopt(long, value_name = "NAME")]
     bin: Option<String>,
@@ -175,7 +245,7 @@ pub struct Opt {
 
     // See the comment of `script_arg` above.
     #[structopt(skip)]
-    script: Option<Mutex<File>>,
+    script: Option<Arc<Mutex<File>>>,
 }
 
 arg_enum! {
@@ -184,6 +254,7 @@ arg_enum! {
         Proptest,
         Klee,
         Seahorn,
+        Crux,
     }
 }
 
@@ -198,6 +269,22 @@ pub enum Status {
     Panic,
     Reachable,
     Timeout,
+    Vacuous, // No path completed, e.g. the assumptions are unsatisfiable.
+}
+
+impl Status {
+    const ALL: [Status; 10] = [
+        Status::Unknown,
+        Status::Verified,
+        Status::Error,
+        Status::AssertFailed,
+        Status::OutOfBounds,
+        Status::Overflow,
+        Status::Panic,
+        Status::Reachable,
+        Status::Timeout,
+        Status::Vacuous,
+    ];
 }
 
 impl fmt::Display for Status {
@@ -220,6 +307,7 @@ impl fmt::Display for Status {
             Status::Panic => write!(f, "PANIC"),
             Status::Reachable => write!(f, "REACHABLE"),
             Status::Timeout => write!(f, "TIMEOUT"),
+            Status::Vacuous => write!(f, "VACUOUS"),
         }
     }
 }
@@ -296,9 +384,9 @@ fn process_command_line() -> CVResult<Opt> {
 
     if let Some(script) = &opt.script_arg {
         fs::remove_file(script).unwrap_or(());
-        opt.script = Some(Mutex::new(
+        opt.script = Some(Arc::new(Mutex::new(
             OpenOptions::new().create(true).append(true).open(script)?,
-        ));
+        )));
     }
 
     opt.backend = match opt.backend_arg {
@@ -307,6 +395,9 @@ fn process_command_line() -> CVResult<Opt> {
             if opt.output.is_some() {
                 Err("backend proptest does not support --output")?;
             }
+            if opt.timeout.is_some() || opt.max_memory.is_some() {
+                Err("backend proptest does not support --timeout or --max-memory")?;
+            }
             assert!(proptest::check_install());
             Backend::Proptest
         }
@@ -322,6 +413,18 @@ fn process_command_line() -> CVResult<Opt> {
             }
             Backend::Seahorn
         }
+        Some(Backend::Crux) => {
+            if opt.output.is_some() {
+                Err("backend crux does not support --output")?;
+            }
+            if opt.max_memory.is_some() {
+                Err("backend crux does not support --max-memory")?;
+            }
+            if !crux::check_install() {
+                Err("Crux-mir is not installed")?;
+            }
+            Backend::Crux
+        }
         None => {
             // If the user did not specify a backend, use the first one that we find.
             let backend = if klee::check_install() {
@@ -353,11 +456,8 @@ fn process_command_line() -> CVResult<Opt> {
             }
         }
         Backend::Seahorn => {
-            if !opt.args.is_empty() {
-                Err("The Seahorn backend does not support passing arguments yet.")?;
-            }
-            if opt.replay != 0 {
-                Err("The Seahorn backend does not support '--replay' yet.")?;
+            if !opt.args.is_empty() && (opt.tests || !opt.test.is_empty()) {
+                Err("The Seahorn backend does not support passing arguments to tests.")?;
             }
 
             opt.features.push(String::from("verifier-seahorn"));
@@ -365,6 +465,82 @@ fn process_command_line() -> CVResult<Opt> {
         Backend::Klee => {
             opt.features.push(String::from("verifier-klee"));
         }
+        Backend::Crux => {
+            if !opt.args.is_empty() {
+                Err("The Crux backend does not support passing arguments.")?;
+            }
+            if opt.replay != 0 {
+                Err("The Crux backend does not support '--replay'.")?;
+            }
+            if !opt.tests && opt.test.is_empty() {
+                Err("The Crux backend can only verify tests (use '--tests' or '--test').")?;
+            }
+
+            opt.features.push(String::from("verifier-crux"));
+        }
+    }
+
+    if !opt.exclude.is_empty() && !opt.workspace {
+        Err("'--exclude' can only be used together with '--workspace'")?;
+    }
+    if opt.workspace && !opt.package.is_empty() {
+        Err("'--workspace' and '--package' cannot be used together")?;
+    }
+    if (opt.workspace || !opt.package.is_empty()) && (opt.bin.is_some() || opt.output.is_some()) {
+        Err("'--bin' and '--output' cannot be used with '--workspace' or '--package'")?;
+    }
+
+    if opt.regression_tests.is_some() {
+        if opt.backend != Backend::Klee {
+            Err("'--regression-tests' is only supported by the KLEE backend")?;
+        }
+        if !opt.tests && opt.test.is_empty() {
+            Err("'--regression-tests' can only be used with '--tests' or '--test'")?;
+        }
+    }
+
+    if opt.coverage && opt.backend != Backend::Klee {
+        Err("'--coverage' is only supported by the KLEE backend")?;
+    }
+
+    if opt.require_assertions && opt.backend != Backend::Klee {
+        Err("'--require-assertions' is only supported by the KLEE backend")?;
+    }
+
+    if !opt.sym_args.is_empty() || !opt.sym_env.is_empty() || opt.sym_stdin.is_some() {
+        if opt.backend != Backend::Klee {
+            Err("'--sym-args', '--sym-env' and '--sym-stdin' are only supported by the KLEE backend")?;
+        }
+        if opt.tests || !opt.test.is_empty() {
+            Err("'--sym-args', '--sym-env' and '--sym-stdin' cannot be used with '--tests' or '--test'")?;
+        }
+        if opt.sym_env.len() > 64 {
+            Err("At most 64 '--sym-env' variables are supported")?;
+        }
+        for spec in &opt.sym_env {
+            match spec.rsplit_once('=') {
+                Some((var, len)) if !var.is_empty() && len.parse::<usize>().is_ok() => (),
+                _ => Err(format!(
+                    "Invalid '--sym-env {}' (expected VAR=MAXLEN)",
+                    spec
+                ))?,
+            }
+        }
+    }
+
+    if opt.entry.is_some() {
+        if opt.tests || !opt.test.is_empty() {
+            Err("'--entry' cannot be used with '--tests' or '--test'")?;
+        }
+        if opt.workspace || !opt.package.is_empty() || opt.bin.is_some() {
+            Err("'--entry' cannot be used with '--workspace', '--package' or '--bin'")?;
+        }
+        if opt.backend == Backend::Proptest || opt.backend == Backend::Crux {
+            Err(format!(
+                "The {} backend does not support '--entry'.",
+                opt.backend
+            ))?;
+        }
     }
 
     // Use the user specified number of jobs, or the number of CPUs.
@@ -382,28 +558,88 @@ fn main() -> CVResult<()> {
         clean(&opt);
     }
 
-    let package = match &opt.bin {
-        Some(bin) => bin.clone(),
-        None => get_meta_package_name(&opt)?,
+    if opt.jobs > 1 {
+        // `build_global` must not be called more than once!
+        // This call configures the thread-pool for `par_iter` in `verify`.
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(opt.jobs)
+            .build_global()?;
+    }
+
+    // The packages to check, and the options to check them with.
+    let packages = if opt.workspace || !opt.package.is_empty() {
+        get_meta_packages(&opt)?
+            .into_iter()
+            .map(|(package, cargo_toml)| {
+                let opt = Opt {
+                    cargo_toml,
+                    ..opt.clone()
+                };
+                (package, opt)
+            })
+            .collect()
+    } else if let Some(entry) = &opt.entry {
+        let cargo_toml = harness::generate(&opt, entry)?;
+        let opt = Opt {
+            cargo_toml,
+            ..opt.clone()
+        };
+        vec![(harness::HARNESS_PACKAGE.to_string(), opt)]
+    } else {
+        let package = match &opt.bin {
+            Some(bin) => bin.clone(),
+            None => get_meta_package_name(&opt)?,
+        };
+        vec![(package, opt.clone())]
     };
-    info_at!(&opt, Verbosity::Informative, "Checking {}", &package);
 
-    let status = match opt.backend {
-        Backend::Proptest => {
-            info_at!(
-                &opt,
-                Verbosity::Informative,
-                "  Invoking cargo run with proptest backend"
-            );
-            proptest::run(&opt)
+    let mut results = vec![];
+    for (package, opt) in &packages {
+        info_at!(&opt, Verbosity::Informative, "Checking {}", &package);
+
+        let package_results = match opt.backend {
+            Backend::Proptest => {
+                info_at!(
+                    &opt,
+                    Verbosity::Informative,
+                    "  Invoking cargo run with proptest backend"
+                );
+                proptest::run(opt, package)
+            }
+            Backend::Crux => {
+                info_at!(&opt, Verbosity::Informative, "  Invoking cargo crux-test");
+                crux::run(opt, package)
+            }
+            _ => {
+                let target = get_default_host(opt)?;
+                info_at!(&opt, Verbosity::Trivial, "target: {}", target);
+                verify(opt, package, &target)
+            }
         }
-        _ => {
-            let target = get_default_host(&opt)?;
-            info_at!(&opt, Verbosity::Trivial, "target: {}", target);
-            verify(&opt, &package, &target)
+        .unwrap_or_else(|err| {
+            // Report the failure as the result of the package and carry on
+            // with the other packages.
+            error!("{}", err);
+            print_result(opt, package, Status::Unknown);
+            vec![TestResult {
+                message: Some(err.to_string()),
+                ..TestResult::new(opt, package, package, Status::Unknown)
+            }]
+        });
+        results.extend(package_results);
+    }
+
+    if let Some(entry) = &opt.entry {
+        // Report the results of the harness as the results of the library
+        // function.
+        let package = get_meta_package_name(&opt)?;
+        for r in &mut results {
+            r.package = package.clone();
+            r.name = entry.clone();
         }
     }
-    .unwrap_or_else(|err| {
+
+    let status = summarize(&opt, &results).unwrap_or_else(|err| {
         error!("{}", err);
         exit(1)
     });
@@ -417,7 +653,7 @@ fn main() -> CVResult<()> {
 
 /// Compile a Rust crate to generate bitcode and run one of the LLVM verifier
 /// backends on the result.
-fn verify(opt: &Opt, package: &str, target: &str) -> CVResult<Status> {
+fn verify(opt: &Opt, package: &str, target: &str) -> CVResult<Vec<TestResult>> {
     let beginning = Instant::now();
 
     // Compile and link the patched file using LTO to generate the entire
@@ -479,6 +715,11 @@ fn verify(opt: &Opt, package: &str, target: &str) -> CVResult<Status> {
         // Find the entry function (mangled main)
         let mains = mangle_functions(&opt, &bcfile, &[String::from(package) + "::main"])?;
         match mains.as_slice() {
+            // With arguments, the entry is a function that rvt-patch-llvm
+            // generated to set up the arguments and call main.
+            [(name, _)] if !opt.args.is_empty() => {
+                vec![(name.clone(), seahorn::SEAHORN_MAIN.to_string())]
+            }
             [(_, _)] => mains,
             [] => Err("  FAILED: can't find the 'main' function")?,
             _ => Err("  FAILED: found more than one 'main' function")?,
@@ -511,44 +752,35 @@ fn verify(opt: &Opt, package: &str, target: &str) -> CVResult<Status> {
 
     let before_verifier = Instant::now();
 
-    let results: Vec<Status> = if opt.jobs > 1 {
-        // Run the verification in parallel.
-
-        // `build_global` must not be called more than once!
-        // This call configures the thread-pool for `par_iter` below.
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(opt.jobs)
-            .build_global()?;
+    // The cache is not used when replaying, reporting coverage or generating
+    // regression tests as they need the output of the backend.
+    let cache = if opt.cache && opt.replay == 0 && !opt.coverage && opt.regression_tests.is_none() {
+        Some(cache::Cache::new(opt)?)
+    } else {
+        None
+    };
 
+    let results: Vec<TestResult> = if opt.jobs > 1 {
+        // Run the verification in parallel (the thread-pool is configured in
+        // `main`).
         tests
             .par_iter() // <- parallelised iterator
-            .map(|(name, entry)| verifier_run(&opt, &bcfile, &name, &entry))
+            .map(|(name, entry)| {
+                verifier_run(&opt, cache.as_ref(), &bcfile, package, &name, &entry)
+            })
             .collect()
     } else {
         // Same as above but without the overhead of rayon
         tests
             .iter() // <- this is the only difference
-            .map(|(name, entry)| verifier_run(&opt, &bcfile, &name, &entry))
+            .map(|(name, entry)| {
+                verifier_run(&opt, cache.as_ref(), &bcfile, package, &name, &entry)
+            })
             .collect()
     };
 
-    // Count pass/fail
-    let passes = results.iter().filter(|r| **r == Status::Verified).count();
-    let fails = results.len() - passes;
-    // randomly pick one failing status (if any)
-    let status = results
-        .into_iter()
-        .find(|r| *r != Status::Verified)
-        .unwrap_or(Status::Verified);
-
     let end = Instant::now();
 
-    // Note use of \n to end line of results in --quiet mode
-    println!(
-        "\ntest result: {:#}. {} passed; {} failed",
-        status, passes, fails
-    );
-
     info_at!(
         &opt,
         Verbosity::Informative,
@@ -568,23 +800,99 @@ fn verify(opt: &Opt, package: &str, target: &str) -> CVResult<Status> {
         end.duration_since(beginning).as_secs_f32()
     );
 
-    Ok(status)
+    Ok(results)
 }
 
 /// Invoke one of the supported verification backends on entry point 'entry'
-/// (with pretty name 'name') in bitcodefile 'bcfile'.
-fn verifier_run(opt: &Opt, bcfile: &Path, name: &str, entry: &str) -> Status {
-    let status = match opt.backend {
+/// (with pretty name 'name') in bitcodefile 'bcfile', unless the result is in
+/// 'cache'.
+fn verifier_run(
+    opt: &Opt,
+    cache: Option<&cache::Cache>,
+    bcfile: &Path,
+    package: &str,
+    name: &str,
+    entry: &str,
+) -> TestResult {
+    let start = Instant::now();
+
+    let cached = cache
+        .map(|cache| cache.lookup(opt, bcfile, entry))
+        .transpose();
+    let (cached, cache_path) = match cached {
+        Ok(Some((outcome, path))) => (outcome, Some(path)),
+        Ok(None) => (None, None),
+        Err(err) => {
+            warn!("Cannot use the cache for '{}': {}", name, err);
+            (None, None)
+        }
+    };
+    let from_cache = cached.is_some();
+    if from_cache {
+        info_at!(
+            &opt,
+            Verbosity::Informative,
+            "  Using cached result for {}",
+            name
+        );
+    }
+
+    let outcome = cached.unwrap_or_else(|| backend_run(opt, bcfile, name, entry));
+
+    if let (Some(cache), Some(path), false) = (cache, cache_path, from_cache) {
+        if let Err(err) = cache.store(&path, &outcome) {
+            warn!(
+                "Cannot store the result of '{}' in the cache: {}",
+                name, err
+            );
+        }
+    }
+
+    let time = start.elapsed();
+    let status = outcome.status;
+
+    print_result(opt, name, status);
+    print_cover_points(opt, status, &outcome.cover);
+
+    TestResult {
+        entry: entry.to_string(),
+        message: outcome.message,
+        time,
+        stats: outcome.stats,
+        ktest_files: outcome.ktest_files,
+        err_files: outcome.err_files,
+        cover: outcome.cover,
+        cached: from_cache,
+        ..TestResult::new(opt, package, name, status)
+    }
+}
+
+/// Invoke one of the supported verification backends on entry point 'entry'.
+fn backend_run(opt: &Opt, bcfile: &Path, name: &str, entry: &str) -> backends_common::Outcome {
+    match opt.backend {
         Backend::Klee => klee::verify(&opt, &name, &entry, &bcfile),
         Backend::Seahorn => seahorn::verify(&opt, &name, &entry, &bcfile),
-        Backend::Proptest => unreachable!(),
+        Backend::Proptest | Backend::Crux => unreachable!(),
     }
     .unwrap_or_else(|err| {
-        error!("{}", err);
-        error!("Failed to run test '{}'.", name);
-        Status::Unknown
-    });
+        if err.is::<Timeout>() {
+            let message = backends_common::timeout_message(opt);
+            error!("{}: {}", name, message);
+            backends_common::Outcome {
+                message: Some(message),
+                ..backends_common::Outcome::new(Status::Timeout)
+            }
+        } else {
+            error!("{}", err);
+            error!("Failed to run test '{}'.", name);
+            backends_common::Outcome::new(Status::Unknown)
+        }
+    })
+}
 
+/// Print the result of a single test (one line, or one character in --quiet
+/// mode).
+fn print_result(opt: &Opt, name: &str, status: Status) {
     let mut stdout = io::stdout();
     if opt.quiet {
         let s = match status {
@@ -597,13 +905,59 @@ fn verifier_run(opt: &Opt, bcfile: &Path, name: &str, entry: &str) -> Status {
             Status::Panic => Colour::Red.paint("P"),
             Status::Reachable => Colour::Red.paint("R"),
             Status::Timeout => Colour::Red.paint("T"),
+            Status::Vacuous => Colour::Yellow.paint("V"),
         };
         write!(stdout, "{}", s).unwrap();
     } else {
         writeln!(stdout, "test {} ... {:#}", name, status).unwrap();
     }
     stdout.flush().unwrap();
-    status
+}
+
+/// Print which cover points (see `verifier::cover!`) a test hit.
+/// If the test was verified, the exploration was complete so the cover
+/// points that were not hit are unreachable.
+fn print_cover_points(opt: &Opt, status: Status, cover: &BTreeMap<String, bool>) {
+    if opt.quiet {
+        return;
+    }
+    for (label, hit) in cover {
+        let result = if *hit {
+            "hit"
+        } else if status == Status::Verified {
+            "unreachable"
+        } else {
+            "not hit"
+        };
+        println!("    cover {} ... {}", label, result);
+    }
+}
+
+/// Print the pass/fail counts of all the tests, write the requested reports,
+/// and return the overall status.
+fn summarize(opt: &Opt, results: &[TestResult]) -> CVResult<Status> {
+    // Count pass/fail
+    let passes = results
+        .iter()
+        .filter(|r| r.status == Status::Verified)
+        .count();
+    let fails = results.len() - passes;
+    // randomly pick one failing status (if any)
+    let status = results
+        .iter()
+        .map(|r| r.status)
+        .find(|r| *r != Status::Verified)
+        .unwrap_or(Status::Verified);
+
+    report::write_reports(opt, status, results)?;
+
+    // Note use of \n to end line of results in --quiet mode
+    println!(
+        "\ntest result: {:#}. {} passed; {} failed",
+        status, passes, fails
+    );
+
+    Ok(status)
 }
 
 /// Compile, link and do transformations on LLVM bitcode.
@@ -652,7 +1006,13 @@ fn build(opt: &Opt, package: &str, target: &str) -> CVResult<PathBuf> {
     if opt.backend == Backend::Seahorn {
         info_at!(&opt, Verbosity::Major, "  Patching LLVM file for Seahorn");
         let new_bc_file = add_pre_ext(&bc_file, "patch-sea");
-        patch_llvm(&opt, &["--seahorn"], &bc_file, &new_bc_file)?;
+        let mut options = vec![String::from("--seahorn")];
+        if !opt.args.is_empty() {
+            // Pass the arguments to main (see process_command_line).
+            options.push(format!("--entry={}::main", package));
+            options.extend(opt.args.iter().map(|arg| format!("--arg={}", arg)));
+        }
+        patch_llvm(opt, &options, &bc_file, &new_bc_file)?;
         bc_file = new_bc_file;
     }
 
@@ -709,6 +1069,14 @@ fn get_build_envs(opt: &Opt) -> CVResult<Vec<(String, String)>> {
         rustflags.push_str(" -Clink-arg=-Wl,--defsym=klee_is_symbolic=0");
     }
 
+    if opt.backend == Backend::Klee || opt.backend == Backend::Seahorn {
+        // The file system model (used by verifier::fs) is part of the
+        // runtime library that is linked with the bitcode file (not with the
+        // executable).
+        rustflags.push_str(" -Clink-arg=-Wl,--defsym=__rvt_fs_add_file=0");
+        rustflags.push_str(" -Clink-arg=-Wl,--defsym=__rvt_fs_set_stdin=0");
+    }
+
     match std::env::var_os("RUSTFLAGS") {
         Some(env) => {
             rustflags.push_str(" ");
@@ -792,7 +1160,7 @@ fn compile(opt: &Opt, package: &str, target: &str) -> CVResult<(PathBuf, Vec<Pat
             if opt.tests || !opt.test.is_empty() {
                 Err("  FAILED: Use --tests with library crates")?
             } else {
-                Err(format!("  FAILED: Test {} unable to find the right bitcode file - should you have used --tests?", &package))?
+                Err(format!("  FAILED: Test {} unable to find the right bitcode file - should you have used --tests or --entry?", &package))?
             }
         }
         _ => {
@@ -836,7 +1204,12 @@ fn compile(opt: &Opt, package: &str, target: &str) -> CVResult<(PathBuf, Vec<Pat
 ///   work)
 /// - redirecting panic! to invoke backend-specific intrinsic functions for
 ///   reporting errors
-fn patch_llvm(opt: &Opt, options: &[&str], bcfile: &Path, new_bcfile: &Path) -> CVResult<()> {
+fn patch_llvm(
+    opt: &Opt,
+    options: &[impl AsRef<OsStr>],
+    bcfile: &Path,
+    new_bcfile: &Path,
+) -> CVResult<()> {
     Command::new("rvt-patch-llvm")
         .arg(bcfile)
         .arg("-o")
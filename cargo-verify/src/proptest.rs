@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::time::Duration;
+
 use log::warn;
 
 use crate::*;
@@ -15,7 +17,7 @@ pub fn check_install() -> bool {
 }
 
 /// Run cargo test
-pub fn run(opt: &Opt) -> CVResult<Status> {
+pub fn run(opt: &Opt, package: &str) -> CVResult<Vec<TestResult>> {
     let mut cmd = Command::new("cargo");
     cmd.arg("test")
         .arg("--manifest-path")
@@ -34,29 +36,97 @@ pub fn run(opt: &Opt) -> CVResult<Status> {
         cmd.arg("--test").arg(t);
     }
 
+    let mut test_args = vec![];
     if opt.replay > 0 {
         assert!(opt.args.is_empty());
-        cmd.arg("--").arg("--nocapture");
-    } else if !opt.args.is_empty() {
-        cmd.arg("--").args(&opt.args);
+        test_args.push("--nocapture");
+    } else {
+        test_args.extend(opt.args.iter().map(String::as_str));
+    }
+    if opt.report.is_some() || opt.junit.is_some() {
+        // Ask the test harness for the duration of each test.
+        test_args.extend(&["-Zunstable-options", "--report-time"]);
+    }
+    if !test_args.is_empty() {
+        cmd.arg("--").args(&test_args);
     }
 
-    match cmd.output_info_ignore_exit(&opt, Verbosity::Major) {
+    let (status, mut results) = match cmd.output_info_ignore_exit(&opt, Verbosity::Major) {
         Err(e) => {
             warn!("Proptest failed '{:?}'", e);
-            Ok(Status::Error)
+            (Status::Error, vec![])
         }
-        Ok((_, stderr, success)) => {
-            if !success {
-                for l in stderr.lines() {
-                    if l.contains("with overflow") {
-                        return Ok(Status::Overflow);
-                    }
-                }
-                Ok(Status::Error)
+        Ok((stdout, stderr, success)) => {
+            let status = if success {
+                Status::Verified
+            } else if stderr.lines().any(|l| l.contains("with overflow")) {
+                Status::Overflow
             } else {
-                Ok(Status::Verified)
-            }
+                Status::Error
+            };
+            (status, test_results(opt, package, &stdout))
         }
+    };
+
+    // Make sure that a failure that is not attributed to any test (e.g., a
+    // compilation error) is not lost.
+    if status != Status::Verified && results.iter().all(|r| r.status == Status::Verified) {
+        results.push(TestResult::new(opt, package, package, status));
+    }
+
+    Ok(results)
+}
+
+/// Extract the result of each test from the output of `cargo test`.
+fn test_results(opt: &Opt, package: &str, stdout: &str) -> Vec<TestResult> {
+    lazy_static! {
+        // test NAME ... STATUS [<TIMEs>]
+        static ref RESULT: Regex =
+            Regex::new(r"^test (\S+) \.\.\. (ok|FAILED)(?: <([0-9.]+)s>)?$").unwrap();
+    }
+
+    stdout
+        .lines()
+        .filter_map(|l| RESULT.captures(l))
+        .map(|caps| {
+            let name = caps.get(1).unwrap().as_str();
+            let time = caps
+                .get(3)
+                .and_then(|t| t.as_str().parse::<f64>().ok())
+                .map_or(Duration::default(), Duration::from_secs_f64);
+            let (status, message) = if caps.get(2).unwrap().as_str() == "ok" {
+                (Status::Verified, None)
+            } else {
+                let message = failure_message(stdout, name);
+                (
+                    backends_common::panic_status(message.as_deref().unwrap_or_default()),
+                    message,
+                )
+            };
+
+            TestResult {
+                message,
+                time,
+                ..TestResult::new(opt, package, name, status)
+            }
+        })
+        .collect()
+}
+
+/// Find the output captured by the test harness for the failing test `name`.
+fn failure_message(stdout: &str, name: &str) -> Option<String> {
+    let header = format!("---- {} stdout ----", name);
+    let message = stdout
+        .lines()
+        .skip_while(|l| *l != header)
+        .skip(1)
+        .take_while(|l| !l.starts_with("---- ") && *l != "failures:")
+        .collect::<Vec<_>>()
+        .join("\n");
+    let message = message.trim();
+    if message.is_empty() {
+        None
+    } else {
+        Some(message.to_string())
     }
 }
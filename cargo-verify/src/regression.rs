@@ -0,0 +1,532 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+////////////////////////////////////////////////////////////////////////////////
+// Regression tests from counterexamples (--regression-tests)
+//
+// When replaying a failing input, the `proptest!` macro prints the value of
+// each parameter ("  Value a = 42").
+// We combine those values with the body of the test to generate a plain
+// `#[test]` that reproduces the failure with `cargo test`.
+//
+// The test is found by parsing the source of the crate (with syn) and
+// following the module path of the test's name.
+// The generated file is included (with `include!`) in the module of the
+// original test so that the test can use everything that the original test
+// could use (even in a private `#[cfg(test)] mod tests`).
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{fs, path::Component};
+
+use log::warn;
+use proc_macro2::LineColumn;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Attribute, Block, Expr, Ident, Item, Pat, Token, Type,
+};
+
+use crate::*;
+
+/// Write a regression test that reproduces the failure of test `name` on the
+/// input `ktest`, using the output of replaying the input.
+/// Returns the path of the generated file (if one was generated).
+pub fn generate(
+    opt: &Opt,
+    dir: &Path,
+    name: &str,
+    status: Status,
+    ktest: &Path,
+    replay_output: &str,
+) -> CVResult<Option<PathBuf>> {
+    lazy_static! {
+        // Printed by `proptest!` when replaying
+        static ref VALUE: Regex = Regex::new(r"^\s*Value (.+?) = (.*)$").unwrap();
+    }
+
+    let values: Vec<(String, &str)> = replay_output
+        .lines()
+        .filter_map(|l| VALUE.captures(l))
+        .map(|caps| {
+            (
+                without_spaces(caps.get(1).unwrap().as_str()),
+                caps.get(2).unwrap().as_str(),
+            )
+        })
+        .collect();
+    if values.is_empty() {
+        warn!(
+            "Cannot generate a regression test for '{}': no input values were printed (is it a proptest! test?)",
+            name
+        );
+        return Ok(None);
+    }
+
+    let found = match find_test(opt, name)? {
+        Some(found) => found,
+        None => {
+            warn!(
+                "Cannot generate a regression test for '{}': cannot find its source",
+                name
+            );
+            return Ok(None);
+        }
+    };
+    let test = &found.test;
+    if test
+        .attrs
+        .iter()
+        .any(|a| without_spaces(a).starts_with("#[should_panic"))
+    {
+        // The inputs that panic are the expected behaviour of the test.
+        return Ok(None);
+    }
+
+    let input = ktest
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let fn_name = name.rsplit("::").next().unwrap_or(name);
+    let test_name = format!("{}_{}", fn_name, input);
+
+    fs::create_dir_all(dir)?;
+    let path = dir.to_path_buf().append(format!("{}.rs", test_name));
+    let include = relative_path(
+        found.file.parent().unwrap_or(Path::new(".")),
+        &fs::canonicalize(dir)?.join(format!("{}.rs", test_name)),
+    )?;
+
+    let mut code = format!(
+        "// Regression test generated by cargo-verify.\n\
+         //\n\
+         // Test:   {}\n\
+         // Input:  {}\n\
+         // Result: {}\n\
+         //\n\
+         // The body of the test was copied from {} and the values of the\n\
+         // parameters are the values that were printed when the input was\n\
+         // replayed.\n\
+         // Include this file in the module of the original test:\n\
+         //\n\
+         //     include!(\"{}\");\n\
+         \n",
+        name,
+        ktest.to_string_lossy(),
+        status,
+        found.file.to_string_lossy(),
+        include.to_string_lossy()
+    );
+    code.push_str("#[test]\n");
+    for attr in &test.attrs {
+        // The attributes of test functions (e.g., `#[ignore]`) are kept.
+        if without_spaces(attr) != "#[test]" {
+            code.push_str(attr);
+            code.push('\n');
+        }
+    }
+    code.push_str(&format!("fn {}() {{\n", test_name));
+    for (parm, ty) in &test.params {
+        let value = match values.iter().find(|(n, _)| *n == without_spaces(parm)) {
+            Some((_, value)) => value,
+            None => Err(format!("no value was printed for parameter '{}'", parm))?,
+        };
+        match ty {
+            Some(ty) => code.push_str(&format!(
+                "    let {}: {} = {};\n",
+                parm,
+                ty,
+                value_expr(value, Some(ty))
+            )),
+            None => code.push_str(&format!(
+                "    let {} = {};\n",
+                parm,
+                value_expr(value, None)
+            )),
+        }
+    }
+    code.push_str(&format!("    {}\n}}\n", test.body));
+
+    fs::write(&path, code)?;
+    Ok(Some(path))
+}
+
+/// The parts of a test function that we need (as source code).
+struct TestSource {
+    /// Attributes (e.g., `#[should_panic]`) of the function.
+    attrs:  Vec<String>,
+    /// Parameter names and types (`a: u32`) or `None` (`a in 0..10u32`).
+    params: Vec<(String, Option<String>)>,
+    /// The body of the function, including the braces.
+    body:   String,
+}
+
+/// A test that was found in the source of the crate.
+struct FoundTest {
+    /// The file that contains the test (canonicalized).
+    file: PathBuf,
+    test: TestSource,
+}
+
+/// Find the source of test `name` (e.g., `tests::overflow`) in the crate by
+/// following its module path from the root of the library or of the binary.
+fn find_test(opt: &Opt, name: &str) -> CVResult<Option<FoundTest>> {
+    let src_dir = opt
+        .cargo_toml
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("src");
+    let mut path: Vec<&str> = name.split("::").collect();
+    let fn_name = path.pop().ok_or("empty test name")?;
+
+    for root in &[src_dir.join("lib.rs"), src_dir.join("main.rs")] {
+        if root.exists() {
+            if let Some(found) = find_in_root(root, &path, fn_name)? {
+                return Ok(Some(found));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Find the `proptest!` test `fn_name` in module `path` of the crate whose
+/// root is `root`.
+fn find_in_root(root: &Path, path: &[&str], fn_name: &str) -> CVResult<Option<FoundTest>> {
+    let mut file = fs::canonicalize(root)?;
+    let mut text = fs::read_to_string(&file)?;
+    let mut items = syn::parse_file(&text)?.items;
+    // The directory of the files of the submodules.
+    let mut dir = file.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+    for module in path {
+        let content = items.iter().find_map(|item| match item {
+            Item::Mod(m) if m.ident == module => Some(m.content.clone()),
+            _ => None,
+        });
+        match content {
+            None => return Ok(None),
+            Some(Some((_, content))) => items = content,
+            Some(None) => {
+                file = dir.join(format!("{}.rs", module));
+                if !file.exists() {
+                    file = dir.join(module).join("mod.rs");
+                }
+                text = fs::read_to_string(&file)?;
+                items = syn::parse_file(&text)?.items;
+            }
+        }
+        dir = dir.join(module);
+    }
+
+    for item in &items {
+        if let Item::Macro(m) = item {
+            if !m.mac.path.is_ident("proptest") {
+                continue;
+            }
+            // Macros that do not parse as tests are ignored.
+            let tests = match syn::parse2::<PropTests>(m.mac.tokens.clone()) {
+                Ok(tests) => tests,
+                Err(_) => continue,
+            };
+            if let Some(test) = tests.0.iter().find(|t| t.name == fn_name) {
+                return Ok(Some(FoundTest {
+                    file,
+                    test: test.source(&text),
+                }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The path of `to` relative to directory `from` (both canonicalized).
+fn relative_path(from: &Path, to: &Path) -> CVResult<PathBuf> {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        Err("the regression test and the original test have no common directory")?
+    }
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    path.extend(&to[common..]);
+    Ok(path)
+}
+
+/// The tests in a `proptest!` macro.
+struct PropTests(Vec<PropTest>);
+
+/// A test in a `proptest!` macro.
+struct PropTest {
+    attrs:  Vec<Attribute>,
+    name:   Ident,
+    params: Vec<Param>,
+    body:   Block,
+}
+
+/// A parameter of a `proptest!` test: `PAT in STRATEGY` or `NAME: TYPE`.
+struct Param {
+    pat: Pat,
+    ty:  Option<Type>,
+}
+
+impl Parse for PropTests {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // E.g., #![proptest_config(...)]
+        input.call(Attribute::parse_inner)?;
+        let mut tests = vec![];
+        while !input.is_empty() {
+            let attrs = input.call(Attribute::parse_outer)?;
+            input.parse::<Token![fn]>()?;
+            let name = input.parse()?;
+            let content;
+            parenthesized!(content in input);
+            let params = content.parse_terminated::<_, Token![,]>(Param::parse)?;
+            let body = input.parse()?;
+            tests.push(PropTest {
+                attrs,
+                name,
+                params: params.into_iter().collect(),
+                body,
+            });
+        }
+        Ok(PropTests(tests))
+    }
+}
+
+impl Parse for Param {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let pat = input.parse()?;
+        if input.peek(Token![in]) {
+            input.parse::<Token![in]>()?;
+            input.parse::<Expr>()?;
+            Ok(Param { pat, ty: None })
+        } else {
+            input.parse::<Token![:]>()?;
+            Ok(Param {
+                pat,
+                ty: Some(input.parse()?),
+            })
+        }
+    }
+}
+
+impl PropTest {
+    /// The source code of the test (which is part of `text`).
+    fn source(&self, text: &str) -> TestSource {
+        let code = |span: proc_macro2::Span| source(text, span.start(), span.end());
+        TestSource {
+            attrs:  self.attrs.iter().map(|a| code(a.span())).collect(),
+            params: self
+                .params
+                .iter()
+                .map(|p| (code(p.pat.span()), p.ty.as_ref().map(|ty| code(ty.span()))))
+                .collect(),
+            body:   code(self.body.span()),
+        }
+    }
+}
+
+/// The part of `text` between `start` and `end`.
+fn source(text: &str, start: LineColumn, end: LineColumn) -> String {
+    // Lines start at 1 and columns (in characters) at 0.
+    let offset = |pos: LineColumn| {
+        let line_start: usize = text
+            .split_inclusive('\n')
+            .take(pos.line - 1)
+            .map(str::len)
+            .sum();
+        line_start
+            + text[line_start..]
+                .char_indices()
+                .nth(pos.column)
+                .map_or(text.len() - line_start, |(i, _)| i)
+    };
+    text[offset(start)..offset(end)].to_string()
+}
+
+/// `s` without whitespace (to compare code).
+fn without_spaces(s: &str) -> String {
+    s.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Convert a value printed with `{:?}` to a Rust expression.
+///
+/// This works for most values of the types that strategies generate but the
+/// generated code may need some editing for other types.
+fn value_expr(value: &str, ty: Option<&str>) -> String {
+    let is = |prefix: &str| match ty {
+        Some(ty) => ty.starts_with(prefix),
+        None => true,
+    };
+    if value.starts_with('[') && is("Vec") {
+        format!("vec!{}", value)
+    } else if value.starts_with('"') && is("String") {
+        format!("String::from({})", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE: &str = r#"
+use proptest::prelude::*;
+use crate::helpers::check;
+use super::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn other(a in 0..10u32) {
+        check(a);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow")]
+    fn add((a, b) in (0..10u32, 0u32..), c: Vec<u8>) {
+        let _ = a + b + c.len() as u32; // {
+    }
+}
+"#;
+
+    #[test]
+    fn parse_test() {
+        let items = syn::parse_file(MODULE).unwrap().items;
+        let tests = items
+            .iter()
+            .find_map(|item| match item {
+                Item::Macro(m) => syn::parse2::<PropTests>(m.mac.tokens.clone()).ok(),
+                _ => None,
+            })
+            .unwrap();
+        let test = tests
+            .0
+            .iter()
+            .find(|t| t.name == "add")
+            .unwrap()
+            .source(MODULE);
+        assert_eq!(
+            test.attrs,
+            vec!["#[test]", r#"#[should_panic(expected = "overflow")]"#]
+        );
+        assert_eq!(
+            test.params,
+            vec![
+                ("(a, b)".to_string(), None),
+                ("c".to_string(), Some("Vec<u8>".to_string()))
+            ]
+        );
+        assert_eq!(
+            test.body,
+            "{\n        let _ = a + b + c.len() as u32; // {\n    }"
+        );
+    }
+
+    /// A crate with a test in a private module.
+    const LIB: &str = r#"
+// The proptest! tests are only parsed by cargo-verify.
+macro_rules! proptest {
+    ($($t:tt)*) => {};
+}
+
+#[cfg(test)]
+mod tests {
+    fn helper(a: u32) -> u32 {
+        a + 1
+    }
+
+    proptest! {
+        #[test]
+        fn check(a in 0..10u32, v: Vec<u8>) {
+            assert!(helper(a) > a && v.len() < 3);
+        }
+
+        #[test]
+        #[should_panic]
+        fn expected(a: u32) {
+            panic!("{}", a);
+        }
+    }
+
+    include!("../regression/check_test000001.rs");
+}
+"#;
+
+    #[test]
+    fn compile() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-verify-regression-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"regression\"\nversion = \"0.1.0\"\nedition = \"2018\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src").join("lib.rs"), LIB).unwrap();
+        let cargo_toml = dir.join("Cargo.toml");
+        let opt = Opt::from_iter(&[
+            "cargo-verify",
+            "--manifest-path",
+            cargo_toml.to_str().unwrap(),
+        ]);
+        let ktest = Path::new("test000001.ktest");
+        let output = "  Value a = 3\n  Value v = [1, 2]\n";
+
+        let generate = |name| {
+            generate(
+                &opt,
+                &dir.join("regression"),
+                name,
+                Status::AssertFailed,
+                ktest,
+                output,
+            )
+            .unwrap()
+        };
+        // The inputs of tests that should panic are not failures.
+        assert!(generate("tests::expected").is_none());
+        let path = generate("tests::check").unwrap();
+        let code = fs::read_to_string(&path).unwrap();
+        assert!(code.contains(r#"include!("../regression/check_test000001.rs");"#));
+
+        let result = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+            .arg("test")
+            .arg("--offline")
+            .arg("--manifest-path")
+            .arg(&cargo_toml)
+            .output()
+            .unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        assert!(
+            result.status.success(),
+            "{}{}",
+            stdout,
+            String::from_utf8_lossy(&result.stderr)
+        );
+        assert!(stdout.contains("test tests::check_test000001 ... ok"));
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(
+            relative_path(Path::new("/a/src/x"), Path::new("/a/regression/t.rs")).unwrap(),
+            PathBuf::from("../../regression/t.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("/a/src"), Path::new("/a/src/t.rs")).unwrap(),
+            PathBuf::from("t.rs")
+        );
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+////////////////////////////////////////////////////////////////////////////////
+// Machine-readable reports of verification results (for CI, dashboards, etc.)
+////////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{CVResult, Opt, Status, Verbosity};
+
+/// The result of verifying a single entry point.
+#[derive(Debug, Serialize)]
+pub struct TestResult {
+    /// The package that contains the test.
+    pub package:     String,
+    /// Name of the test (without the package prefix).
+    pub name:        String,
+    /// Mangled name of the entry function.
+    pub entry:       String,
+    /// The backend that verified the test.
+    pub backend:     String,
+    pub status:      Status,
+    /// The backend output that explains a failure (e.g. a panic message).
+    pub message:     Option<String>,
+    /// Wall time spent in the backend.
+    #[serde(rename = "time_secs", serialize_with = "serialize_secs")]
+    pub time:        Duration,
+    #[serde(serialize_with = "serialize_sorted")]
+    pub stats:       HashMap<String, isize>,
+    pub ktest_files: Vec<PathBuf>,
+    pub err_files:   Vec<PathBuf>,
+    /// The cover points that were reached and whether they were hit.
+    pub cover:       BTreeMap<String, bool>,
+    /// The result was taken from the cache (see --cache).
+    pub cached:      bool,
+}
+
+impl TestResult {
+    /// A result with no details (which can be filled in using struct update
+    /// syntax).
+    pub fn new(opt: &Opt, package: &str, name: &str, status: Status) -> Self {
+        TestResult {
+            package: package.to_string(),
+            name: name.to_string(),
+            entry: name.to_string(),
+            backend: opt.backend.to_string().to_lowercase(),
+            status,
+            message: None,
+            time: Duration::default(),
+            stats: HashMap::new(),
+            ktest_files: vec![],
+            err_files: vec![],
+            cover: BTreeMap::new(),
+            cached: false,
+        }
+    }
+}
+
+/// The top level of the JSON report.
+#[derive(Serialize)]
+struct Report<'a> {
+    status: Status,
+    passed: usize,
+    failed: usize,
+    tests:  &'a [TestResult],
+}
+
+/// Statuses are reported using the same strings as the "VERIFICATION_RESULT:"
+/// line.
+impl Serialize for Status {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Status {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Status::ALL
+            .iter()
+            .copied()
+            .find(|status| status.to_string() == s)
+            .ok_or_else(|| de::Error::custom(format!("unknown status '{}'", s)))
+    }
+}
+
+fn serialize_secs<S: Serializer>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(d.as_secs_f64())
+}
+
+/// Serialize a `HashMap` with its keys sorted (so that reports are stable).
+fn serialize_sorted<S: Serializer>(
+    map: &HashMap<String, isize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Write the reports requested on the command line.
+pub fn write_reports(opt: &Opt, status: Status, results: &[TestResult]) -> CVResult<()> {
+    if let Some(path) = &opt.report {
+        write_json(path, status, results)?;
+        info_at!(
+            &opt,
+            Verbosity::Informative,
+            "Wrote report to {}",
+            path.to_string_lossy()
+        );
+    }
+
+    if let Some(path) = &opt.junit {
+        write_junit(path, results)?;
+        info_at!(
+            &opt,
+            Verbosity::Informative,
+            "Wrote JUnit report to {}",
+            path.to_string_lossy()
+        );
+    }
+
+    Ok(())
+}
+
+/// Write a JSON report with one record per test to `path`.
+fn write_json(path: &Path, status: Status, results: &[TestResult]) -> CVResult<()> {
+    let passed = results
+        .iter()
+        .filter(|r| r.status == Status::Verified)
+        .count();
+    let report = Report {
+        status,
+        passed,
+        failed: results.len() - passed,
+        tests: results,
+    };
+
+    let file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(file, &report)?;
+    Ok(())
+}
+
+/// Which JUnit element (if any) reports a test with status `status`.
+///
+/// Property violations are "failures", problems that prevented us from getting
+/// an answer are "errors".
+fn junit_element(status: Status) -> Option<&'static str> {
+    match status {
+        Status::Verified => None,
+        Status::Unknown | Status::Timeout | Status::Vacuous => Some("error"),
+        Status::Error
+        | Status::AssertFailed
+        | Status::OutOfBounds
+        | Status::Overflow
+        | Status::Panic
+        | Status::Reachable => Some("failure"),
+    }
+}
+
+/// Escape the characters that are special in XML attributes and text.
+fn xml_escape(s: &str) -> String {
+    let mut r = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => r.push_str("&amp;"),
+            '<' => r.push_str("&lt;"),
+            '>' => r.push_str("&gt;"),
+            '"' => r.push_str("&quot;"),
+            '\'' => r.push_str("&apos;"),
+            c => r.push(c),
+        }
+    }
+    r
+}
+
+/// Write a JUnit XML report with one testsuite per package and one testcase
+/// per test to `path`.
+fn write_junit(path: &Path, results: &[TestResult]) -> CVResult<()> {
+    // Group the results by package (keeping the order of the packages).
+    let mut packages: Vec<(&str, Vec<&TestResult>)> = vec![];
+    for r in results {
+        match packages.iter_mut().find(|(p, _)| *p == r.package) {
+            Some((_, rs)) => rs.push(r),
+            None => packages.push((&r.package, vec![r])),
+        }
+    }
+
+    let (failures, errors, time) = junit_counts(results.iter());
+
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    #[rustfmt::skip]
+    writeln!(file, r#"<testsuites tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+             results.len(), failures, errors, time)?;
+
+    for (package, results) in packages {
+        let (failures, errors, time) = junit_counts(results.iter().copied());
+        let package = xml_escape(package);
+        #[rustfmt::skip]
+        writeln!(file, r#"  <testsuite name="{}" tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+                 package, results.len(), failures, errors, time)?;
+
+        for r in results {
+            write!(
+                file,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+                xml_escape(&r.name),
+                package,
+                r.time.as_secs_f64()
+            )?;
+            match junit_element(r.status) {
+                None => writeln!(file, "/>")?,
+                Some(element) => {
+                    let message = r.message.as_deref().unwrap_or_default();
+                    writeln!(file, ">")?;
+                    #[rustfmt::skip]
+                    writeln!(file, r#"      <{} type="{}" message="{}">{}</{}>"#,
+                             element, r.status,
+                             xml_escape(message.lines().next().unwrap_or_default()),
+                             xml_escape(message), element)?;
+                    writeln!(file, "    </testcase>")?;
+                }
+            }
+        }
+
+        writeln!(file, "  </testsuite>")?;
+    }
+
+    writeln!(file, "</testsuites>")?;
+    Ok(())
+}
+
+/// The number of failures and errors, and the total time of `results`.
+fn junit_counts<'a>(results: impl Iterator<Item = &'a TestResult> + Clone) -> (usize, usize, f64) {
+    let count = |element| {
+        results
+            .clone()
+            .filter(|r| junit_element(r.status) == Some(element))
+            .count()
+    };
+    let time = results.clone().map(|r| r.time).sum::<Duration>();
+    (count("failure"), count("error"), time.as_secs_f64())
+}
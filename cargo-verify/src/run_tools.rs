@@ -1,9 +1,37 @@
-use std::{io::Write, iter, str::Lines};
-
-use log::info;
+use std::{
+    io::{Read, Write},
+    iter,
+    os::unix::process::CommandExt,
+    process::{Output, Stdio},
+    str::Lines,
+    thread,
+    time::Duration,
+};
+
+use log::{info, warn};
 
 use crate::*;
 
+/// The error returned when a command was killed because it ran out of time.
+#[derive(Debug)]
+pub struct Timeout {
+    pub program: String,
+    pub limit:   Duration,
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FAILED: '{}' was killed after {}s.",
+            self.program,
+            self.limit.as_secs()
+        )
+    }
+}
+
+impl error::Error for Timeout {}
+
 /// Trait for wrapping `std::process::Command::output()` with logging.
 pub trait OutputInfo {
     fn output_info(&mut self, opt: &Opt, lvl: Verbosity) -> CVResult<(String, String)> {
@@ -12,12 +40,13 @@ pub trait OutputInfo {
             lvl,
             |v| String::from(from_utf8(v).expect("not UTF-8")),
             false,
+            None,
         )
         .map(|(stdout, stderr, _)| (stdout, stderr))
     }
 
     fn latin1_output_info(&mut self, opt: &Opt, lvl: Verbosity) -> CVResult<(String, String)> {
-        self.output_info_helper(&opt, lvl, utils::from_latin1, false)
+        self.output_info_helper(&opt, lvl, utils::from_latin1, false, None)
             .map(|(stdout, stderr, _)| (stdout, stderr))
     }
 
@@ -31,15 +60,35 @@ pub trait OutputInfo {
             lvl,
             |v| String::from(from_utf8(v).expect("not UTF-8")),
             true,
+            None,
+        )
+    }
+
+    /// Like `output_info_ignore_exit` but kill the command (and return a
+    /// `Timeout` error) if it is still running after `timeout`.
+    fn output_info_timeout(
+        &mut self,
+        opt: &Opt,
+        lvl: Verbosity,
+        timeout: Option<Duration>,
+    ) -> CVResult<(String, String, bool)> {
+        self.output_info_helper(
+            opt,
+            lvl,
+            |v| String::from(from_utf8(v).expect("not UTF-8")),
+            true,
+            timeout,
         )
     }
 
-    fn latin1_output_info_ignore_exit(
+    /// Latin-1 version of `output_info_timeout`.
+    fn latin1_output_info_timeout(
         &mut self,
         opt: &Opt,
         lvl: Verbosity,
+        timeout: Option<Duration>,
     ) -> CVResult<(String, String, bool)> {
-        self.output_info_helper(&opt, lvl, utils::from_latin1, true)
+        self.output_info_helper(opt, lvl, utils::from_latin1, true, timeout)
     }
 
     fn output_info_helper(
@@ -48,6 +97,7 @@ pub trait OutputInfo {
         lvl: Verbosity,
         trans: impl Fn(&[u8]) -> String,
         ignore_exit: bool,
+        timeout: Option<Duration>,
     ) -> CVResult<(String, String, bool)>;
 }
 
@@ -58,6 +108,7 @@ impl OutputInfo for Command {
         lvl: Verbosity,
         trans: impl Fn(&[u8]) -> String,
         ignore_exit: bool,
+        timeout: Option<Duration>,
     ) -> CVResult<(String, String, bool)> {
         info_cmd(&self);
 
@@ -65,7 +116,10 @@ impl OutputInfo for Command {
             eprintln!("Cannot write to script: {:?}", e);
         }
 
-        let output = self.output()?;
+        let (output, timed_out) = match timeout {
+            None => (self.output()?, false),
+            Some(timeout) => output_with_timeout(self, timeout)?,
+        };
 
         let stdout = trans(&output.stdout);
         info_lines(&opt, lvl, "STDOUT: ", stdout.lines());
@@ -73,6 +127,13 @@ impl OutputInfo for Command {
         let stderr = trans(&output.stderr);
         info_lines(&opt, lvl, "STDERR: ", stderr.lines());
 
+        if timed_out {
+            Err(Timeout {
+                program: self.get_program().to_string_lossy().into(),
+                limit:   timeout.unwrap_or_default(),
+            })?
+        }
+
         if !ignore_exit && !output.status.success() {
             match output.status.code() {
                 Some(code) => Err(format!(
@@ -91,6 +152,65 @@ impl OutputInfo for Command {
     }
 }
 
+/// Run `cmd` to completion (like `Command::output`) unless it takes longer than
+/// `timeout`, in which case it is killed.
+/// Returns the output and whether the command was killed.
+fn output_with_timeout(cmd: &mut Command, timeout: Duration) -> CVResult<(Output, bool)> {
+    // Run the command in its own process group so that we can kill any
+    // processes that it starts as well.
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()?;
+
+    // Read the pipes on separate threads so that the child does not block
+    // when a pipe is full.
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = vec![];
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut buf).unwrap_or_default();
+            }
+            buf
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            warn!(
+                "Killing process {} after {}s",
+                child.id(),
+                timeout.as_secs()
+            );
+            timed_out = true;
+            Command::new("kill")
+                .arg("-KILL")
+                .arg(format!("-{}", child.id()))
+                .status()
+                .ok(); // Discarding the error on purpose (we kill the child below anyway).
+            child.kill().unwrap_or_default();
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let output = Output {
+        status,
+        stdout: stdout.join().map_err(|_| "Cannot read stdout")?,
+        stderr: stderr.join().map_err(|_| "Cannot read stderr")?,
+    };
+    Ok((output, timed_out))
+}
+
 /// Log `cmd` nicely.
 fn info_cmd(cmd: &Command) {
     info!(
@@ -215,17 +335,61 @@ pub fn get_meta_package_name(opt: &Opt) -> CVResult<String> {
         .root_package()
         .ok_or("no root package")?
         .name
-        .replace(
-            |c| match c {
-                // Allowed characters.
-                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' => false,
-                // Anything else will be replaced with the '_' character.
-                _ => true,
-            },
-            "_",
-        );
+        .clone();
+
+    Ok(crate_name(&name))
+}
+
+/// Find the workspace packages selected by '--workspace', '--package' and
+/// '--exclude'.
+/// Returns the name of the crate and the path to the Cargo.toml of each
+/// package.
+pub fn get_meta_packages(opt: &Opt) -> CVResult<Vec<(String, PathBuf)>> {
+    let metadata = MetadataCommand::new()
+        .manifest_path(&opt.cargo_toml)
+        .features(CargoOpt::SomeFeatures(opt.features.clone()))
+        .exec()?;
+
+    let members: Vec<_> = metadata
+        .packages
+        .iter()
+        .filter(|p| metadata.workspace_members.contains(&p.id))
+        .collect();
+
+    for spec in opt.package.iter().chain(&opt.exclude) {
+        if !members.iter().any(|p| &p.name == spec) {
+            Err(format!(
+                "package '{}' is not a member of the workspace",
+                spec
+            ))?;
+        }
+    }
+
+    let packages: Vec<_> = members
+        .into_iter()
+        .filter(|p| opt.workspace || opt.package.contains(&p.name))
+        .filter(|p| !opt.exclude.contains(&p.name))
+        .map(|p| (crate_name(&p.name), p.manifest_path.clone()))
+        .collect();
+
+    if packages.is_empty() {
+        Err("no packages to verify")?;
+    }
+    Ok(packages)
+}
 
-    Ok(name)
+/// Convert a package name to the name of the crate (as used in file and
+/// function names).
+fn crate_name(package: &str) -> String {
+    package.replace(
+        |c| match c {
+            // Allowed characters.
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' => false,
+            // Anything else will be replaced with the '_' character.
+            _ => true,
+        },
+        "_",
+    )
 }
 
 /// Find the target directory.
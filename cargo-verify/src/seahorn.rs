@@ -6,11 +6,15 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::{ffi::OsString, fs, path::Path, process::Command};
+use std::{collections::HashMap, ffi::OsString, fs, path::Path, process::Command};
 
 use log::{info, warn};
 
-use crate::{utils::Append, *};
+use crate::{backends_common::Outcome, utils::Append, *};
+
+/// The entry function that `rvt-patch-llvm --entry` generates to pass
+/// arguments to main.
+pub const SEAHORN_MAIN: &str = "rvt_seahorn_main";
 
 /// Check if Seahorn is avilable.
 pub fn check_install() -> bool {
@@ -24,7 +28,7 @@ pub fn check_install() -> bool {
 }
 
 /// Run Seahorn
-pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Status> {
+pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Outcome> {
     let out_dir = opt.cargo_toml.with_file_name("seaout").append(name);
 
     // Ignoring result. We don't care if it fails because the path doesn't
@@ -43,7 +47,32 @@ pub fn verify(opt: &Opt, name: &str, entry: &str, bcfile: &Path) -> CVResult<Sta
     info!("      entry: {}", entry);
     info!("      results: {}", out_dir.to_string_lossy());
 
-    run(&opt, &name, &entry, &bcfile, &out_dir)
+    let (status, stats) = run(&opt, &name, &entry, &bcfile, &out_dir)?;
+    if !stats.is_empty() {
+        info!("     {}: {:?}", name, stats);
+    }
+
+    if opt.replay > 0 && status != Status::Verified {
+        let harness = out_dir.clone().append("harness.ll");
+        if harness.exists() {
+            println!("    Counterexample {}", harness.to_string_lossy());
+            match replay(opt, entry, bcfile, &out_dir, &harness) {
+                Ok(output) => {
+                    for line in output.lines() {
+                        println!("{}", line);
+                    }
+                }
+                Err(err) => warn!("Failed to replay: {}", err),
+            }
+        } else {
+            warn!("Seahorn did not generate a counterexample for {}", name);
+        }
+    }
+
+    Ok(Outcome {
+        stats,
+        ..Outcome::new(status)
+    })
 }
 
 /// Return an int indicating importance of a line from KLEE's output
@@ -66,7 +95,11 @@ fn importance(line: &str, expect: &Option<&str>, name: &str) -> i8 {
             && line.ends_with("possible reads of undefined values"))
     {
         4
-    } else if backends_common::is_expected_panic(&line, &expect, &name) || line == "unsat" {
+    } else if backends_common::is_expected_panic(&line, &expect, &name)
+        || line == "unsat"
+        || line.starts_with("BRUNCH_STAT")
+        || line.contains("BRUNCH STATS")
+    {
         5
     } else if line.starts_with("Warning:") {
         // Really high priority to force me to categorize it
@@ -77,8 +110,45 @@ fn importance(line: &str, expect: &Option<&str>, name: &str) -> i8 {
     }
 }
 
+/// The largest bound of the `verifier::loop_bound!` annotations with a
+/// literal bound in the sources of the crate (if any).
+///
+/// Loops must be unrolled once more than this to detect that a loop exceeds
+/// its bound.
+fn max_loop_bound(opt: &Opt) -> CVResult<Option<usize>> {
+    let crate_dir = opt.cargo_toml.parent().ok_or("no crate directory")?;
+    let mut bound = None;
+    for dir in &["src", "tests"] {
+        let pattern = glob::Pattern::escape(crate_dir.to_str().ok_or("not UTF-8")?)
+            .append("/")
+            .append(dir)
+            .append("/**/*.rs");
+        for file in glob::glob(&pattern)? {
+            let source = fs::read_to_string(file?)?;
+            bound = bound.max(loop_bounds(&source).max());
+        }
+    }
+    Ok(bound)
+}
+
+/// The literal bounds of the `loop_bound!` annotations in `source`.
+fn loop_bounds(source: &str) -> impl Iterator<Item = usize> + '_ {
+    lazy_static! {
+        static ref LOOP_BOUND: Regex = Regex::new(r"loop_bound!\s*\(\s*(\d+)\s*,").unwrap();
+    }
+    LOOP_BOUND
+        .captures_iter(source)
+        .filter_map(|caps| caps[1].parse().ok())
+}
+
 /// Run Seahorn and analyse its output.
-fn run(opt: &Opt, name: &str, entry: &str, bcfile: &Path, out_dir: &Path) -> CVResult<Status> {
+fn run(
+    opt: &Opt,
+    name: &str,
+    entry: &str,
+    bcfile: &Path,
+    out_dir: &Path,
+) -> CVResult<(Status, HashMap<String, isize>)> {
     let verify_common_dir = match &opt.seahorn_verify_c_common_dir {
         Some(verify_common_dir) => verify_common_dir,
         None => Err("The '--seahorn-verify-c-common-dir' option is missing")?,
@@ -99,13 +169,42 @@ fn run(opt: &Opt, name: &str, entry: &str, bcfile: &Path, out_dir: &Path) -> CVR
             .arg("bpf")
             .arg(OsString::from("--temp-dir=").append(out_dir))
             .arg(String::from("--entry=") + entry)
-            .args(user_flags)
-            .arg(&bcfile);
+            // Print statistics (solving time, number of VCs, etc.)
+            .arg("--horn-stats");
+
+        if let Some(secs) = opt.timeout {
+            cmd.arg(format!("--cpu={}", secs));
+        }
+        if let Some(mb) = opt.max_memory {
+            cmd.arg(format!("--mem={}", mb));
+        }
+
+        // Unroll loops far enough to check their `loop_bound!` annotations
+        // (unless a bound was given with '--backend-flags').
+        if !user_flags.iter().any(|flag| flag.starts_with("--bound")) {
+            if let Some(bound) = max_loop_bound(opt)? {
+                info!("      bound: {} (from loop_bound!)", bound + 1);
+                cmd.arg(format!("--bound={}", bound + 1));
+            }
+        }
+
+        if opt.replay > 0 {
+            // Write a counterexample harness: definitions of the
+            // __VERIFIER_nondet_* functions that return the values that
+            // lead to the failure.
+            cmd.arg(OsString::from("--cex=").append(out_dir.to_path_buf().append("harness.ll")));
+        }
+
+        cmd.args(user_flags).arg(&bcfile);
     } else {
         cmd.args(user_flags);
     }
 
-    let (stdout, stderr, _) = cmd.output_info_ignore_exit(&opt, Verbosity::Major)?;
+    let (stdout, stderr, _) = cmd.output_info_timeout(
+        &opt,
+        Verbosity::Major,
+        backends_common::watchdog_timeout(opt),
+    )?;
 
     // We scan stderr for:
     // 1. Indications of the expected output (eg from #[should_panic])
@@ -159,7 +258,8 @@ fn run(opt: &Opt, name: &str, entry: &str, bcfile: &Path, out_dir: &Path) -> CVR
         expect.unwrap_or("---")
     );
 
-    // TODO: Scan for statistics
+    // Scan for statistics
+    let stats = statistics(&stderr);
 
     for l in stderr.lines() {
         if importance(&l, &expect, &name) < opt.verbose as i8 {
@@ -167,5 +267,165 @@ fn run(opt: &Opt, name: &str, entry: &str, bcfile: &Path, out_dir: &Path) -> CVR
         }
     }
 
-    Ok(status)
+    Ok((status, stats))
+}
+
+/// Extract the statistics that Seahorn prints with `--horn-stats`:
+///
+///   BRUNCH_STAT name value
+///
+/// Counters are integers and timers are in seconds; timers are converted to
+/// milliseconds and get the suffix "_ms".
+fn statistics(stderr: &str) -> HashMap<String, isize> {
+    lazy_static! {
+        static ref BRUNCH_STAT: Regex = Regex::new(r"^BRUNCH_STAT\s+(\S+)\s+(\S+)\s*$").unwrap();
+    }
+
+    stderr
+        .lines()
+        .filter_map(|l| BRUNCH_STAT.captures(l))
+        .filter_map(|caps| {
+            let name = caps.get(1).unwrap().as_str();
+            let value = caps.get(2).unwrap().as_str();
+            // If the value doesn't parse we throw the line.
+            if let Ok(v) = value.parse::<isize>() {
+                Some((name.to_string(), v))
+            } else {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .map(|v| (format!("{}_ms", name), (v * 1000.0).round() as isize))
+            }
+        })
+        .collect()
+}
+
+/// The Seahorn API used when replaying counterexamples.
+const REPLAY_DRIVER: &str = r#"// Generated by cargo-verify, do not edit.
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+unsigned char __VERIFIER_is_replay = 1;
+
+void __VERIFIER_error(void) {
+  fprintf(stderr, "ERROR: a verification assertion failed.\n");
+  exit(1);
+}
+
+void __VERIFIER_assume(int pred) {
+  if (pred == 0) {
+    fprintf(stderr, "ERROR: a verification assumption has been violated.\n");
+    exit(1);
+  }
+}
+
+#define NONDET(type, name) __attribute__((weak)) type __VERIFIER_nondet_##name(void) { return 0; }
+NONDET(uint8_t, u8)
+NONDET(uint16_t, u16)
+NONDET(uint32_t, u32)
+NONDET(uint64_t, u64)
+NONDET(uintptr_t, usize)
+NONDET(int8_t, i8)
+NONDET(int16_t, i16)
+NONDET(int32_t, i32)
+NONDET(int64_t, i64)
+NONDET(intptr_t, isize)
+NONDET(float, f32)
+NONDET(double, f64)
+
+"#;
+
+/// The main function of the replay driver (when the program does not have
+/// arguments).
+const REPLAY_MAIN: &str = r#"
+extern void entry(void) __asm__("{entry}");
+
+int main(void) {
+  entry();
+  return 0;
+}
+"#;
+
+/// Replay a Seahorn counterexample
+/// Link the counterexample `harness` with the program, run `entry` natively and
+/// return its output.
+fn replay(
+    opt: &Opt,
+    entry: &str,
+    bcfile: &Path,
+    out_dir: &Path,
+    harness: &Path,
+) -> CVResult<String> {
+    // The bitcode file before it was patched for Seahorn (the patches remove
+    // `main` and the printing functions).
+    // See `build` in main.rs.
+    let bcfile = bcfile.with_extension("").with_extension("bc");
+
+    // The replay driver tells `verifier::is_replay` that this is a replay
+    // and implements the rest of the Seahorn API.
+    // (The counterexample harness overrides the __VERIFIER_nondet_*
+    // functions that were used on the path to the failure.)
+    // If the program has arguments, we use the original main function (which
+    // passes the arguments to the program), otherwise the driver calls the
+    // entry function.
+    let driver = out_dir.to_path_buf().append("replay.c");
+    let mut driver_code = String::from(REPLAY_DRIVER);
+    if opt.args.is_empty() {
+        driver_code.push_str(&REPLAY_MAIN.replace("{entry}", entry));
+    }
+    fs::write(&driver, driver_code)?;
+    let driver_bc = driver.with_extension("bc");
+    Command::new(format!("clang-{}", opt.llvm_version))
+        .arg("-c")
+        .arg("-emit-llvm")
+        .arg("-o")
+        .arg(&driver_bc)
+        .arg(&driver)
+        .latin1_output_info(opt, Verbosity::Major)?;
+
+    let replay_bc = out_dir.to_path_buf().append("replay.bc");
+    Command::new(format!("llvm-link-{}", opt.llvm_version))
+        .arg("-o")
+        .arg(&replay_bc)
+        .arg(&bcfile)
+        .arg("--override")
+        .arg(&driver_bc)
+        .arg("--override")
+        .arg(harness)
+        .latin1_output_info(opt, Verbosity::Major)?;
+
+    let exe = out_dir.to_path_buf().append("replay");
+    Command::new(format!("clang-{}", opt.llvm_version))
+        .arg("-o")
+        .arg(&exe)
+        .arg(&replay_bc)
+        .args(["-lm", "-lpthread", "-ldl"])
+        .latin1_output_info(opt, Verbosity::Major)?;
+
+    // Note that we do not treat this as an error, because
+    // the interesting case for replay is when Seahorn had found an error.
+    let (stdout, stderr, _success) = Command::new(&exe)
+        .args(&opt.args)
+        .output_info_ignore_exit(opt, Verbosity::Major)?;
+
+    Ok(stdout + &stderr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        let source = "
+            verifier::loop_bound!(8, for i in 0..n {});
+            verifier::loop_bound!(
+                12,
+                while i < n {}
+            );
+            verifier::loop_bound!(N, loop {});
+        ";
+        assert_eq!(loop_bounds(source).collect::<Vec<_>>(), vec![8, 12]);
+    }
 }
@@ -117,6 +117,81 @@ proptest! {
     }
 }
 
+proptest! {
+    #[test]
+    fn hash_map(v in prop::collection::hash_map(-5..5i32, 10..20u32, 5)) {
+
+        // Note that key collisions may reduce the number of entries
+        // so the following assertion will fail.
+        // assert!(v.len() == 5);
+        assert!(v.len() <= 5);
+
+        for (key, value) in v.iter() {
+            assert!((-5..5i32).contains(key));
+            assert!((*value) > 5);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    #[should_panic(expected = "assertion failed")]
+    fn hash_map_fail1(v in prop::collection::hash_map(-5..5i32, 10..20u32, 5)) {
+        for (key, _) in v.iter() {
+            assert!((0..5i32).contains(key));
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn hash_set(v in prop::collection::hash_set(-100..100i32, 5)) {
+
+        // Note that key collisions may reduce the number of entries
+        // so the following assertion will fail.
+        // assert!(v.len() == 5);
+        assert!(v.len() <= 5);
+
+        for x in v.iter() {
+            assert!((-100..100i32).contains(x));
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    #[should_panic(expected = "assertion failed")]
+    fn hash_set_fail1(v in prop::collection::hash_set(-100..100i32, 5)) {
+        for x in v.iter() {
+            assert!((0..100i32).contains(x));
+        }
+    }
+}
+
+// proptest does not have a deterministic hasher
+#[cfg(verify)]
+proptest! {
+    #[test]
+    fn hash_map_deterministic(v in prop::collection::hash_map(0..10u32, 0..10u32, 3)) {
+        // The default hasher is deterministic when verifying.
+        let v: std::collections::HashMap<u32, u32, prop::collection::DeterministicState> = v;
+        assert!(v.len() <= 3);
+        for (key, value) in v.iter() {
+            assert!(*key < 10 && *value < 10);
+        }
+
+        // Maps built from the same entries with two instances of the
+        // (deterministic) hasher iterate in the same order.
+        let mut entries: Vec<(u32, u32)> = v.into_iter().collect();
+        entries.sort_unstable();
+        let m1: std::collections::HashMap<u32, u32, prop::collection::DeterministicState> =
+            entries.iter().cloned().collect();
+        let m2: std::collections::HashMap<u32, u32, prop::collection::DeterministicState> =
+            entries.iter().cloned().collect();
+        assert!(m1.iter().eq(m2.iter()));
+    }
+}
+
 proptest! {
     #[test]
     fn linked_list(v in prop::collection::linked_list(0..10u32, 5)) {
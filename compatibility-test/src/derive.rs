@@ -0,0 +1,91 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+////////////////////////////////////////////////////////////////
+// Proptest-based tests of #[derive(Arbitrary)]
+////////////////////////////////////////////////////////////////
+
+#[cfg(not(verify))]
+use proptest::prelude::*;
+#[cfg(not(verify))]
+use proptest_derive::Arbitrary;
+#[cfg(verify)]
+use propverify::prelude::*;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Debug, Arbitrary)]
+    struct Point {
+        #[proptest(strategy = "0..10u32")]
+        x: u32,
+        #[proptest(value = "42")]
+        y: i32,
+        z: u8,
+    }
+
+    #[derive(Clone, Debug, Arbitrary)]
+    struct Wrapper<T>(T);
+
+    #[derive(Clone, Debug, Arbitrary)]
+    enum Shape {
+        Empty,
+        Dot(Point),
+        Line {
+            from: Point,
+            #[proptest(strategy = "1..5u8")]
+            length: u8,
+        },
+        #[proptest(skip)]
+        Unused(u32),
+    }
+
+    proptest! {
+        #[test]
+        fn derive_struct(p in any::<Point>()) {
+            assert!(p.x < 10);
+            assert_eq!(p.y, 42);
+            assert!(u32::from(p.z) <= 255);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn derive_generic(w in any::<Wrapper<u16>>()) {
+            assert!(u32::from(w.0) <= 65535);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn derive_enum(s in any::<Shape>()) {
+            match s {
+                Shape::Empty => (),
+                Shape::Dot(p) => assert!(p.x < 10),
+                Shape::Line { from, length } => {
+                    assert!(from.x < 10);
+                    assert!((1..5).contains(&length));
+                }
+                Shape::Unused(_) => unreachable!(),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        #[should_panic(expected = "assertion failed")]
+        fn derive_struct_fail1(p in any::<Point>()) {
+            assert!(p.x < 9);
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////
+// End
+////////////////////////////////////////////////////////////////
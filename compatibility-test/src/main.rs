@@ -23,6 +23,7 @@ use propverify::prelude::*;
 
 mod collections;
 mod compose;
+mod derive;
 mod dynamic;
 mod enumeration;
 
@@ -13,3 +13,13 @@ fn t1() {
     let r = a * b;
     verifier::assert!(1 <= r && r < 1000000);
 }
+
+/// Cover points: the first one is hit and the second one is unreachable
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+fn t2() {
+    let a = u32::abstract_value();
+    verifier::assume(a <= 10);
+    verifier::cover!(a == 10, "a is 10");
+    verifier::cover!(a > 10, "a is greater than 10");
+}
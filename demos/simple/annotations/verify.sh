@@ -9,6 +9,11 @@ cargo clean
 cargo-verify --tests --verbose | tee out1 || true
 grep -q -F "test t1 ... ASSERT_FAILED" out1
 
+# check the cover points
+grep -q -F "test t2 ... OK" out1
+grep -q -F "cover a is 10 ... hit" out1
+grep -q -F "cover a is greater than 10 ... unreachable" out1
+
 # replay input values
 cargo-verify --tests --replay | tee out2 || true
 grep -q -F "Test values: a = 1000, b = 1000" out2
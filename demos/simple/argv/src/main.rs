@@ -16,6 +16,8 @@ use std::env;
 ///    `cargo verify .`
 ///    `cargo verify . --`
 ///    `cargo verify . -- foo bar`
+///
+/// (Add `--backend=seahorn` to verify with SeaHorn instead of KLEE.)
 fn main() {
     println!("{} args", env::args().len());
     verifier::assert!(env::args().len() >= 2);
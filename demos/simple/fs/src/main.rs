@@ -0,0 +1,27 @@
+use std::fs::File;
+use std::io::Read;
+
+#[cfg(verify)]
+use propverify::prelude::*;
+
+/// Virtual files.
+///
+/// Should fail (and replaying the failing input should fail in the same way):
+///    `cargo verify --replay`
+///
+/// The file "secret.txt" does not exist: it is a virtual file with symbolic
+/// contents both during verification and when replaying the failing input.
+fn main() {
+    #[cfg(verify)]
+    verifier::fs::add_symbolic_file("secret.txt", 4);
+
+    let mut contents = String::new();
+    File::open("secret.txt")
+        .expect("open the virtual file")
+        .read_to_string(&mut contents)
+        .expect("read the virtual file");
+
+    if contents == "hi" {
+        panic!("found the secret contents");
+    }
+}
@@ -0,0 +1,23 @@
+#!/bin/bash
+
+set -e
+
+cargo clean
+
+# replaying needs the native file system model
+make -C ${RVT_DIR}/runtime replay
+
+# this should find the secret contents and replay them
+cargo-verify --backend=klee --replay | tee out || true
+
+# check the failure and its input
+grep -q -F "VERIFICATION_RESULT: PANIC" out
+
+# replaying the input must read the virtual file
+grep -q -F "found the secret contents" out
+if grep -q -F "open the virtual file" out; then
+    echo "Replay did not open the virtual file"
+    exit 1
+fi
+
+echo "Test replayed the virtual file"
@@ -0,0 +1,20 @@
+use std::env;
+
+/// Symbolic arguments and environment variables.
+///
+/// Should fail (and replaying the failing input should fail in the same way):
+///    `cargo verify --sym-args 1 2 --sym-env GREETING=2 --replay -- foo`
+///
+/// The symbolic argument comes before the concrete argument "foo"
+/// and the environment only contains the symbolic variable GREETING.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[1], "foo");
+    assert!(env::var("HOME").is_err());
+
+    let greeting = env::var("GREETING").unwrap_or_default();
+    if args[0] == "hi" && greeting == "yo" {
+        panic!("found the secret argument");
+    }
+}
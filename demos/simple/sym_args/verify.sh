@@ -0,0 +1,22 @@
+#!/bin/bash
+
+set -e
+
+cargo clean
+
+# this should find the secret argument and replay it
+cargo-verify --backend=klee --sym-args 1 2 --sym-env GREETING=2 --replay -- foo | tee out || true
+
+# check the failure and its input
+grep -q -F "VERIFICATION_RESULT: PANIC" out
+grep -q -F "argv[1]: CString = \"hi\"" out
+grep -q -F "\$GREETING: CString = \"yo\"" out
+
+# replaying the input must not fail an assertion about the arguments or the
+# environment
+if grep -q -F "assertion failed" out; then
+    echo "Replay did not see the same arguments and environment"
+    exit 1
+fi
+
+echo "Test replayed the symbolic arguments"
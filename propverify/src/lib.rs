@@ -28,6 +28,7 @@ pub mod prelude {
 
     // Arbitrary trait
     pub use crate::strategy::{any, Arbitrary};
+    pub use verification_macros::Arbitrary;
 
     pub mod prop {
         pub use crate::strategy::prop_is_replay;
@@ -45,6 +46,9 @@ pub mod prelude {
             pub use crate::strategy::linked_list;
             pub use crate::strategy::vec;
             pub use crate::strategy::vec_deque;
+            pub use crate::strategy::DeterministicState;
+            pub use crate::strategy::{hash_map, hash_map_with_hasher};
+            pub use crate::strategy::{hash_set, hash_set_with_hasher};
         }
         pub mod num {
             #[cfg(feature = "float")]
@@ -14,8 +14,9 @@ use std::marker::PhantomData;
 use std::rc::Rc;
 use std::sync::Arc;
 
-use std::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
-// use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
 
 // Trait representing a set of values from which one can be chosen
 //
@@ -183,7 +184,10 @@ macro_rules! proptest {
                   }
               }
           )*
-          $(let $parm = $crate::prelude::Strategy::value(&$strategy);)*
+          // Symbolic values are named after the parameter
+          $(let $parm = $crate::prelude::verifier::with_name(
+              std::stringify!($parm),
+              || $crate::prelude::Strategy::value(&$strategy));)*
 
           #[cfg(not(crux))]
           if prop_is_replay() {
@@ -1046,3 +1050,118 @@ where
 {
     BinaryHeapStrategy { element, size }
 }
+
+// A hasher that always uses the same keys.
+//
+// RandomState seeds each hasher with random keys from the OS which
+// makes hashing different on every run.
+// This makes paths explored by KLEE hard to replay
+// so it is the default hasher of hash_map and hash_set
+// and code under test should use this hasher if it can.
+pub type DeterministicState = BuildHasherDefault<DefaultHasher>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct HashMapStrategy<K: Strategy, V: Strategy, H = DeterministicState> {
+    keys: K,
+    value: V,
+    size: usize, // concrete size to be more friendly to concolic/DSE
+    hasher: H,
+}
+impl<K: Strategy, V: Strategy, H> Strategy for HashMapStrategy<K, V, H>
+where
+    K::Value: Hash + Eq,
+    H: BuildHasher + Clone + std::fmt::Debug,
+{
+    type Value = HashMap<K::Value, V::Value, H>;
+    fn value(&self) -> Self::Value {
+        let len = self.size;
+        let mut r = HashMap::with_capacity_and_hasher(len, self.hasher.clone());
+
+        // Unlike btree_map, keys are not generated in increasing order
+        // because the key type need not be Ord.
+        for _ in 0..len {
+            r.insert(self.keys.value(), self.value.value());
+        }
+        r
+    }
+}
+
+// Unlike proptest, the map uses DeterministicState (instead of RandomState)
+// so that its iteration order is the same on every run.
+// Use hash_map_with_hasher for other hashers (e.g., `RandomState::new()`).
+pub fn hash_map<K: Strategy, V: Strategy>(keys: K, value: V, size: usize) -> HashMapStrategy<K, V>
+where
+    K::Value: Hash + Eq,
+{
+    hash_map_with_hasher(keys, value, size, DeterministicState::default())
+}
+
+// Like hash_map but the map uses `hasher`
+// (e.g., `RandomState::new()`).
+pub fn hash_map_with_hasher<K: Strategy, V: Strategy, H: BuildHasher>(
+    keys: K,
+    value: V,
+    size: usize,
+    hasher: H,
+) -> HashMapStrategy<K, V, H>
+where
+    K::Value: Hash + Eq,
+{
+    HashMapStrategy {
+        keys,
+        value,
+        size,
+        hasher,
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HashSetStrategy<S: Strategy, H = DeterministicState> {
+    element: S,
+    size: usize, // concrete size to be more friendly to concolic/DSE
+    hasher: H,
+}
+impl<S: Strategy, H> Strategy for HashSetStrategy<S, H>
+where
+    S::Value: Hash + Eq,
+    H: BuildHasher + Clone + std::fmt::Debug,
+{
+    type Value = HashSet<S::Value, H>;
+    fn value(&self) -> Self::Value {
+        let len = self.size;
+        let mut r = HashSet::with_capacity_and_hasher(len, self.hasher.clone());
+
+        // Unlike btree_set, elements are not generated in increasing order
+        // because the element type need not be Ord.
+        for _ in 0..len {
+            r.insert(self.element.value());
+        }
+        r
+    }
+}
+
+// Unlike proptest, the set uses DeterministicState (see hash_map).
+// Use hash_set_with_hasher for other hashers.
+pub fn hash_set<S: Strategy>(element: S, size: usize) -> HashSetStrategy<S>
+where
+    S::Value: Hash + Eq,
+{
+    hash_set_with_hasher(element, size, DeterministicState::default())
+}
+
+// Like hash_set but the set uses `hasher`
+// (e.g., `RandomState::new()`).
+pub fn hash_set_with_hasher<S: Strategy, H: BuildHasher>(
+    element: S,
+    size: usize,
+    hasher: H,
+) -> HashSetStrategy<S, H>
+where
+    S::Value: Hash + Eq,
+{
+    HashSetStrategy {
+        element,
+        size,
+        hasher,
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright 2021 The Rust verification tools Authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Model of a (read-only) file system of virtual files
+//
+// Virtual files are added with __rvt_fs_add_file (see `verifier::fs` in
+// verification-annotations) and their contents can be concrete or symbolic.
+// Standard input can also be replaced with a virtual file
+// (__rvt_fs_set_stdin).
+// The functions that the Rust standard library uses to read files are
+// implemented for virtual files.
+// Until a virtual file is added, files are opened as usual. After that:
+//
+// - when verifying, there are no other files,
+// - when replaying (compiled with RVT_REPLAY, and linked with
+//   "-Wl,--wrap=open64,..."), other files are passed on to the C library.
+//
+// Other file descriptors (including standard input if it was not replaced)
+// are always passed on to the real functions.
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdarg.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/types.h>
+#include <unistd.h>
+
+#ifdef RVT_REPLAY
+#define MODEL(name) __wrap_##name
+#define REAL(name) __real_##name
+int REAL(open)(const char *path, int flags, ...);
+int REAL(open64)(const char *path, int flags, ...);
+ssize_t REAL(read)(int fd, void *buf, size_t count);
+int REAL(close)(int fd);
+off64_t REAL(lseek64)(int fd, off64_t offset, int whence);
+int REAL(fstat64)(int fd, struct stat64 *buf);
+#else
+// When verifying, the real functions are the system calls (which KLEE
+// executes as external calls).
+#define MODEL(name) name
+#define REAL(name) real_##name
+static int REAL(open)(const char *path, int flags, mode_t mode) {
+  return syscall(SYS_openat, AT_FDCWD, path, flags, mode);
+}
+static int REAL(open64)(const char *path, int flags, mode_t mode) {
+  return syscall(SYS_openat, AT_FDCWD, path, flags, mode);
+}
+static ssize_t REAL(read)(int fd, void *buf, size_t count) {
+  return syscall(SYS_read, fd, buf, count);
+}
+static int REAL(close)(int fd) { return syscall(SYS_close, fd); }
+static off64_t REAL(lseek64)(int fd, off64_t offset, int whence) {
+  return syscall(SYS_lseek, fd, offset, whence);
+}
+static int REAL(fstat64)(int fd, struct stat64 *buf) {
+  return syscall(SYS_fstat, fd, buf);
+}
+#endif
+
+#if defined(RVT_klee)
+extern void klee_report_error(const char *file, int line, const char *message,
+                              const char *suffix)
+    __attribute__((noreturn));
+#define FS_ERROR(message)                                                     \
+  klee_report_error(__FILE__, __LINE__, message, "user.err")
+#elif defined(RVT_seahorn)
+extern void __VERIFIER_error(void) __attribute__((noreturn));
+#define FS_ERROR(message) __VERIFIER_error()
+#else
+#define FS_ERROR(message) (fprintf(stderr, "%s\n", message), abort())
+#endif
+
+#define MAX_FILES 64
+#define MAX_FDS 64
+
+// File descriptors of virtual files are FIRST_FD, FIRST_FD+1, ...
+#define FIRST_FD 1000
+
+struct file {
+  const char *path;
+  const uint8_t *data;
+  size_t size;
+};
+
+static struct file files[MAX_FILES];
+static int num_files = 0;
+
+struct open_file {
+  struct file *file; // NULL if the descriptor is not in use
+  off64_t offset;
+};
+
+static struct open_file fds[MAX_FDS];
+
+// Standard input (if it was replaced with a virtual file)
+static struct file stdin_file;
+static struct open_file stdin_fd = {NULL, 0};
+
+// Add a virtual file (or replace the contents of a virtual file).
+// The path and data are not copied.
+void __rvt_fs_add_file(const char *path, const uint8_t *data, size_t size) {
+  struct file *f = NULL;
+  for (int i = 0; i < num_files; ++i) {
+    if (strcmp(files[i].path, path) == 0) {
+      f = &files[i];
+    }
+  }
+  if (f == NULL) {
+    if (num_files == MAX_FILES) {
+      FS_ERROR("too many virtual files");
+    }
+    f = &files[num_files++];
+  }
+  f->path = path;
+  f->data = data;
+  f->size = size;
+}
+
+// Replace standard input with a virtual file.
+// The data is not copied.
+void __rvt_fs_set_stdin(const uint8_t *data, size_t size) {
+  stdin_file.path = "<stdin>";
+  stdin_file.data = data;
+  stdin_file.size = size;
+  stdin_fd.file = &stdin_file;
+  stdin_fd.offset = 0;
+}
+
+static struct file *lookup_file(const char *path) {
+  for (int i = 0; i < num_files; ++i) {
+    if (strcmp(files[i].path, path) == 0) {
+      return &files[i];
+    }
+  }
+  return NULL;
+}
+
+static struct open_file *lookup_fd(int fd) {
+  if (fd == STDIN_FILENO) {
+    return stdin_fd.file ? &stdin_fd : NULL;
+  }
+  if (fd < FIRST_FD || fd >= FIRST_FD + MAX_FDS) {
+    return NULL;
+  }
+  struct open_file *o = &fds[fd - FIRST_FD];
+  return o->file ? o : NULL;
+}
+
+static int open_file(struct file *f, int flags) {
+  if ((flags & O_ACCMODE) != O_RDONLY) {
+    errno = EROFS;
+    return -1;
+  }
+  for (int i = 0; i < MAX_FDS; ++i) {
+    if (fds[i].file == NULL) {
+      fds[i].file = f;
+      fds[i].offset = 0;
+      return FIRST_FD + i;
+    }
+  }
+  errno = EMFILE;
+  return -1;
+}
+
+int MODEL(open)(const char *path, int flags, ...) {
+  va_list ap;
+  va_start(ap, flags);
+  mode_t mode = va_arg(ap, mode_t);
+  va_end(ap);
+  if (num_files > 0) {
+    struct file *f = lookup_file(path);
+    if (f) {
+      return open_file(f, flags);
+    }
+#ifndef RVT_REPLAY
+    errno = ENOENT;
+    return -1;
+#endif
+  }
+  return REAL(open)(path, flags, mode);
+}
+
+int MODEL(open64)(const char *path, int flags, ...) {
+  va_list ap;
+  va_start(ap, flags);
+  mode_t mode = va_arg(ap, mode_t);
+  va_end(ap);
+  if (num_files > 0) {
+    struct file *f = lookup_file(path);
+    if (f) {
+      return open_file(f, flags);
+    }
+#ifndef RVT_REPLAY
+    errno = ENOENT;
+    return -1;
+#endif
+  }
+  return REAL(open64)(path, flags, mode);
+}
+
+ssize_t MODEL(read)(int fd, void *buf, size_t count) {
+  struct open_file *o = lookup_fd(fd);
+  if (o) {
+    size_t size = o->file->size;
+    size_t offset = o->offset < (off64_t)size ? o->offset : size;
+    size_t n = count < size - offset ? count : size - offset;
+    memcpy(buf, o->file->data + offset, n);
+    o->offset += n;
+    return n;
+  }
+  return REAL(read)(fd, buf, count);
+}
+
+int MODEL(close)(int fd) {
+  struct open_file *o = lookup_fd(fd);
+  if (o) {
+    o->file = NULL;
+    return 0;
+  }
+  return REAL(close)(fd);
+}
+
+off64_t MODEL(lseek64)(int fd, off64_t offset, int whence) {
+  struct open_file *o = lookup_fd(fd);
+  if (o) {
+    off64_t base = whence == SEEK_SET   ? 0
+                   : whence == SEEK_CUR ? o->offset
+                   : whence == SEEK_END ? (off64_t)o->file->size
+                                        : -1;
+    if (base < 0 || base + offset < 0) {
+      errno = EINVAL;
+      return -1;
+    }
+    o->offset = base + offset;
+    return o->offset;
+  }
+  return REAL(lseek64)(fd, offset, whence);
+}
+
+int MODEL(fstat64)(int fd, struct stat64 *buf) {
+  struct open_file *o = lookup_fd(fd);
+  if (o) {
+    memset(buf, 0, sizeof(*buf));
+    buf->st_mode = S_IFREG | 0444;
+    buf->st_nlink = 1;
+    buf->st_size = o->file->size;
+    buf->st_blksize = 4096;
+    return 0;
+  }
+  return REAL(fstat64)(fd, buf);
+}
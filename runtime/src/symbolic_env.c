@@ -0,0 +1,161 @@
+// Copyright 2021 The Rust verification tools Authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Symbolic command line arguments and environment variables (KLEE only)
+//
+// cargo-verify passes these options to the program (before any other
+// arguments):
+//
+//   --rvt-sym-args N MAXLEN   add N symbolic arguments of up to MAXLEN characters
+//   --rvt-sym-env VAR=MAXLEN  make VAR a symbolic string of up to MAXLEN characters
+//   --rvt-sym-stdin N         make standard input N symbolic bytes (see fs.c)
+//
+// rvt-patch-llvm --initializers arranges for main to call __rvt_init_args
+// before the initializers (which pass argc/argv to the Rust standard library).
+//
+// The symbolic strings are ASCII strings and the objects are named so that
+// cargo-verify can pass their values to the program when replaying a test.
+
+#ifdef RVT_klee
+
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+
+extern void klee_make_symbolic(void *addr, size_t nbytes, const char *name);
+extern void klee_assume(uintptr_t condition);
+extern void klee_report_error(const char *file, int line, const char *message,
+                              const char *suffix)
+    __attribute__((noreturn));
+extern void __rvt_fs_set_stdin(const uint8_t *data, size_t size);
+
+#define MAX_SYM_ENV 64
+
+static struct {
+  const char *name;
+  char *value;
+} sym_env[MAX_SYM_ENV];
+static int num_sym_env = 0;
+
+// Concatenate three strings (the result is never freed)
+static char *concat(const char *a, const char *b, const char *c) {
+  char *r = malloc(strlen(a) + strlen(b) + strlen(c) + 1);
+  strcpy(r, a);
+  strcat(r, b);
+  strcat(r, c);
+  return r;
+}
+
+static char *decimal(int n) {
+  char buffer[16];
+  char *p = buffer + sizeof(buffer) - 1;
+  *p = 0;
+  do {
+    *--p = '0' + n % 10;
+    n /= 10;
+  } while (n > 0);
+  return concat(p, "", "");
+}
+
+// A symbolic ASCII string of up to max_len characters
+static char *symbolic_string(const char *name, size_t max_len) {
+  char *s = malloc(max_len + 1);
+  klee_make_symbolic(s, max_len + 1, name);
+  for (size_t i = 0; i < max_len; ++i) {
+    klee_assume((unsigned char)s[i] < 128);
+  }
+  klee_assume(s[max_len] == 0);
+  return s;
+}
+
+void __rvt_init_args(int *argcp, char ***argvp) {
+  int argc = *argcp;
+  char **argv = *argvp;
+
+  int num_sym_args = 0;
+  for (int i = 0; i + 2 < argc; ++i) {
+    if (strcmp(argv[i], "--rvt-sym-args") == 0) {
+      num_sym_args += atoi(argv[i + 1]);
+    }
+  }
+
+  char **new_argv = malloc((argc + num_sym_args + 1) * sizeof(char *));
+  int new_argc = 0;
+  for (int i = 0; i < argc; ++i) {
+    if (strcmp(argv[i], "--rvt-sym-args") == 0 && i + 2 < argc) {
+      int n = atoi(argv[i + 1]);
+      int max_len = atoi(argv[i + 2]);
+      i += 2;
+      for (int j = 0; j < n; ++j) {
+        char *name = concat("argv[", decimal(new_argc), "]: CString");
+        new_argv[new_argc] = symbolic_string(name, max_len);
+        ++new_argc;
+      }
+    } else if (strcmp(argv[i], "--rvt-sym-env") == 0 && i + 1 < argc) {
+      if (num_sym_env == MAX_SYM_ENV) {
+        klee_report_error(__FILE__, __LINE__, "too many symbolic variables",
+                          "user.err");
+      }
+      const char *spec = argv[++i];
+      const char *eq = strrchr(spec, '=');
+      if (eq == NULL) {
+        continue;
+      }
+      char *var = malloc(eq - spec + 1);
+      memcpy(var, spec, eq - spec);
+      var[eq - spec] = 0;
+      sym_env[num_sym_env].name = var;
+      sym_env[num_sym_env].value =
+          symbolic_string(concat("$", var, ": CString"), atoi(eq + 1));
+      ++num_sym_env;
+    } else if (strcmp(argv[i], "--rvt-sym-stdin") == 0 && i + 1 < argc) {
+      size_t n = atoi(argv[++i]);
+      uint8_t *data = malloc(n);
+      klee_make_symbolic(data, n, "stdin: Vec<u8>");
+      __rvt_fs_set_stdin(data, n);
+    } else {
+      new_argv[new_argc] = argv[i];
+      ++new_argc;
+    }
+  }
+  new_argv[new_argc] = NULL;
+
+  *argcp = new_argc;
+  *argvp = new_argv;
+}
+
+// The environment of the program (if the C library has one)
+extern char **environ __attribute__((weak));
+
+// If there are symbolic variables, the environment only contains the
+// symbolic variables (and cargo-verify replays tests with an environment
+// that only contains the symbolic variables).
+// Otherwise, the environment of the program is used.
+char *getenv(const char *name) {
+  if (num_sym_env == 0) {
+    if (&environ == NULL || environ == NULL) {
+      return NULL;
+    }
+    size_t len = strlen(name);
+    for (char **e = environ; *e != NULL; ++e) {
+      if (strncmp(*e, name, len) == 0 && (*e)[len] == '=') {
+        return *e + len + 1;
+      }
+    }
+    return NULL;
+  }
+  for (int i = 0; i < num_sym_env; ++i) {
+    if (strcmp(name, sym_env[i].name) == 0) {
+      return sym_env[i].value;
+    }
+  }
+  return NULL;
+}
+
+#endif // RVT_klee
@@ -61,6 +61,16 @@ struct Opt {
     #[structopt(long, conflicts_with = "initializers")]
     seahorn: bool,
 
+    /// Generate a SeaHorn entry function (named 'rvt_seahorn_main') that
+    /// calls the initializers with the program arguments (see --arg) and then
+    /// calls this function (e.g., 'foo::main')
+    #[structopt(long, requires = "seahorn")]
+    entry: Option<String>,
+
+    /// Program argument (concrete) passed to the initializers (see --entry)
+    #[structopt(long = "arg", name = "ARG", requires = "entry", number_of_values = 1)]
+    args: Vec<String>,
+
     /// Increase message verbosity
     #[structopt(short, long, parse(from_occurrences))]
     verbosity: usize,
@@ -141,6 +151,11 @@ fn main() {
     if opt.seahorn {
         handle_main(&module);
 
+        if let Some(entry) = &opt.entry {
+            let program = opt.input.to_string_lossy();
+            handle_seahorn_args(&context, &mut module, entry, &program, &opt.args);
+        }
+
         handle_panic(&context, &module);
 
         replace_def_with_dec(
@@ -172,24 +187,66 @@ fn main() {
 ////////////////////////////////////////////////////////////////
 
 fn handle_initializers(context: &Context, module: &mut Module) {
-    if let Some(initializer) =
-        collect_initializers(context, module, ".init_array", "__init_function")
-    {
+    let initializer = collect_initializers(context, module, ".init_array", "__init_function");
+    let init_args = module
+        .get_function("__rvt_init_args")
+        .filter(|f| f.count_basic_blocks() > 0);
+
+    // Only modules that need a call inserted into main must have one.
+    if initializer.is_none() && init_args.is_none() {
+        info!("No initializers to handle");
+        return;
+    }
+
+    let main = module
+        .get_function("main")
+        .expect("Unable to find 'main' function");
+    let mut args = get_fn_args(main);
+    assert!(args.len() == 2); // We expect "i32 @main(i32 %0, i8** %1)"
+
+    let first_instruction = main
+        .get_first_basic_block()
+        .and_then(|bb| bb.get_first_instruction())
+        .expect("Unable to find where to insert function call into function");
+    let builder = context.create_builder();
+    builder.position_before(&first_instruction);
+
+    // Let the runtime library rewrite the arguments before the initializers
+    // see them (this is used for symbolic arguments).
+    //
+    //     %argc = alloca i32
+    //     store i32 %0, i32* %argc
+    //     %argv = alloca i8**
+    //     store i8** %1, i8*** %argv
+    //     call void @__rvt_init_args(i32* %argc, i8*** %argv)
+    //     %2 = load i32, i32* %argc
+    //     %3 = load i8**, i8*** %argv
+    if let Some(init_args) = init_args {
+        let ptrs: Vec<PointerValue> = args
+            .iter()
+            .map(|arg| {
+                let ptr = builder.build_alloca(arg.get_type(), "");
+                builder.build_store(ptr, *arg);
+                ptr
+            })
+            .collect();
+        let ptr_args: Vec<BasicValueEnum> = ptrs.iter().map(|p| p.as_basic_value_enum()).collect();
+        builder.build_call(init_args, &ptr_args, "");
+        args = ptrs.iter().map(|p| builder.build_load(*p, "")).collect();
+        info!("Inserted call to '__rvt_init_args' into 'main'");
+    }
+
+    if let Some(initializer) = initializer {
         info!(
             "Combined .init_array* initializers into '{}'",
             initializer.get_name().to_str().unwrap()
         );
 
-        let main = module
-            .get_function("main")
-            .expect("Unable to find 'main' function");
-        let mut args = get_fn_args(main);
-        assert!(args.len() == 2); // We expect "i32 @main(i32 %0, i8** %1)"
         let i8_type = context.i8_type();
         let pi8_type = i8_type.ptr_type(AddressSpace::Generic);
         let ppi8_type = pi8_type.ptr_type(AddressSpace::Generic);
         args.push(ppi8_type.const_null().as_basic_value_enum());
-        insert_call_at_head(context, initializer, args, main);
+        builder.build_call(initializer, &args, "");
         info!(
             "Inserted call to '{}' into 'main'",
             initializer.get_name().to_str().unwrap()
@@ -299,23 +356,6 @@ fn get_fn_args<'a>(function: FunctionValue<'a>) -> Vec<BasicValueEnum<'a>> {
         .collect()
 }
 
-fn insert_call_at_head<'a>(
-    context: &Context,
-    f: FunctionValue<'a>,
-    args: Vec<BasicValueEnum<'a>>,
-    insertee: FunctionValue<'a>,
-) {
-    let bb = insertee
-        .get_first_basic_block()
-        .expect("Unable to find function to insert function call into");
-    let first_instruction = bb
-        .get_first_instruction()
-        .expect("Unable to find where to insert function call into function");
-    let builder = context.create_builder();
-    builder.position_before(&first_instruction);
-    builder.build_call(f, &args, "");
-}
-
 ////////////////////////////////////////////////////////////////
 // Function transformation functions
 ////////////////////////////////////////////////////////////////
@@ -379,6 +419,94 @@ fn handle_main(module: &Module) {
     }
 }
 
+/// The name of the entry function generated by `handle_seahorn_args`.
+const SEAHORN_MAIN: &str = "rvt_seahorn_main";
+
+/// Generate an entry function for SeaHorn that calls the initializers with
+/// the program arguments (this makes std::env::args() work) and then calls
+/// `entry`.
+///
+///     @rvt_argv = global [3 x i8*] [i8* @program, i8* @arg1, i8* null]
+///
+///     define void @rvt_seahorn_main() {
+///     entry:
+///       call void @__init_function(i32 2, i8** @rvt_argv, i8** null)
+///       call void @entry()
+///       ret void
+///     }
+///
+/// (This is what KLEE does with `handle_initializers` and the arguments on
+/// its command line.)
+///
+/// The arguments are concrete: unlike KLEE (see `__rvt_init_args`), SeaHorn
+/// does not support symbolic arguments.
+fn handle_seahorn_args(
+    context: &Context,
+    module: &mut Module,
+    entry: &str,
+    program: &str,
+    args: &[String],
+) {
+    let initializer = collect_initializers(context, module, ".init_array", "__init_function");
+    let entry = match get_function_by_unmangled_name(module, entry).as_slice() {
+        [f] => *f,
+        [] => panic!("Unable to find entry function '{}'", entry),
+        _ => panic!("Found more than one entry function '{}'", entry),
+    };
+
+    let i8_type = context.i8_type();
+    let pi8_type = i8_type.ptr_type(AddressSpace::Generic);
+    let ppi8_type = pi8_type.ptr_type(AddressSpace::Generic);
+
+    let function = module.add_function(SEAHORN_MAIN, context.void_type().fn_type(&[], false), None);
+    let basic_block = context.append_basic_block(function, "entry");
+    let builder = context.create_builder();
+    builder.position_at_end(basic_block);
+
+    // argv is a null-terminated array of strings
+    let mut argv: Vec<PointerValue> = std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(|arg| {
+            builder
+                .build_global_string_ptr(arg, "arg")
+                .as_pointer_value()
+        })
+        .collect();
+    let argc = argv.len();
+    argv.push(pi8_type.const_null());
+    let argv_global = module.add_global(pi8_type.array_type(argv.len() as u32), None, "rvt_argv");
+    argv_global.set_initializer(&pi8_type.const_array(&argv));
+
+    if let Some(initializer) = initializer {
+        let argv = builder.build_pointer_cast(argv_global.as_pointer_value(), ppi8_type, "argv");
+        let args = [
+            context
+                .i32_type()
+                .const_int(argc as u64, false)
+                .as_basic_value_enum(),
+            argv.as_basic_value_enum(),
+            ppi8_type.const_null().as_basic_value_enum(),
+        ];
+        builder.build_call(initializer, &args, "");
+        info!(
+            "Inserted call to '{}' into '{}'",
+            initializer.get_name().to_str().unwrap(),
+            SEAHORN_MAIN
+        );
+    } else {
+        info!("No initializers to handle")
+    }
+
+    builder.build_call(entry, &[], "");
+    builder.build_return(None);
+    info!(
+        "Generated '{}' that calls '{}' with {} arguments",
+        SEAHORN_MAIN,
+        entry.get_name().to_str().unwrap(),
+        argc
+    );
+}
+
 /// Find a function whose name matches regex `re`
 fn get_function_by_regex<'ctx>(module: &'ctx Module, re: &Regex) -> Vec<FunctionValue<'ctx>> {
     get_function(module, |name| re.is_match(name))
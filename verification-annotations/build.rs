@@ -8,6 +8,9 @@
 
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
+    // cargo-verify sets `--cfg=verify` (which the function contract
+    // attributes use).
+    println!("cargo:rustc-check-cfg=cfg(verify)");
     #[cfg(feature = "verifier-seahorn")]
     seahorn();
 }
@@ -8,10 +8,16 @@
 
 #![feature(cstring_from_vec_with_nul)]
 
+// The function contract attributes refer to this crate by name.
+extern crate self as verification_annotations;
+
 // Traits for creating symbolic/abstract values
 pub mod traits;
 pub mod verifier;
 
+// Function contracts: `#[requires(...)]` and `#[ensures(...)]`
+pub use verification_macros::{ensures, requires};
+
 pub mod utils {
     pub trait UnwrapOrReject {
         type Wrapped;
@@ -50,7 +56,11 @@ pub mod prelude {
     pub use crate::verifier::assert_eq as verifier_assert_eq;
     pub use crate::verifier::assert_ne as verifier_assert_ne;
     pub use crate::verifier::assume as verifier_assume;
+    pub use crate::verifier::cover as verifier_cover;
     pub use crate::verifier::unreachable as verifier_unreachable;
+
+    // Function contracts
+    pub use crate::{ensures, requires};
 }
 
 // At the moment, the cargo-verify script does not support
@@ -219,6 +219,185 @@ fn string_should_fail() {
     verifier::assert!(i <= 222_222);
 }
 
+/// Test of call_abstract
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn call_abstract_should_fail() {
+    #[cfg(not(feature = "verifier-crux"))]
+    verifier::expect(Some("assertion failed"));
+
+    fn sum(a: u8, b: u8) -> u16 {
+        verifier::assume(a <= 100 && b <= 200);
+        let r = a as u16 + b as u16;
+        verifier::assert!(r < 300);
+        r
+    }
+    verifier::call_abstract(sum);
+}
+
+/// Test of cover! (the first cover point is hit, the second is unreachable)
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn cover() {
+    let a: u32 = verifier::AbstractValue::abstract_value();
+    verifier::assume(a <= 10);
+    verifier::cover!(a == 10, "a is 10");
+    verifier::cover!(a > 10, "a is greater than 10");
+}
+
+/// Test of function contracts
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn contracts() {
+    #[requires(a <= 100 && b <= 100)]
+    #[ensures(ret >= a && ret >= b)]
+    fn add(a: u32, b: u32) -> u32 {
+        a + b
+    }
+
+    let a: u32 = verifier::AbstractValue::abstract_value();
+    let b: u32 = verifier::AbstractValue::abstract_value();
+    add(a, b);
+}
+
+/// Test of function contracts (early exits and values on entry)
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn contracts_exits() {
+    #[ensures(ret <= 10)]
+    fn clamp(a: u32) -> u32 {
+        if a > 10 {
+            return 10;
+        }
+        a
+    }
+
+    #[ensures(ret.is_none() || ret.unwrap() > 0)]
+    fn succ(a: Option<u32>) -> Option<u32> {
+        let a = a?;
+        a.checked_add(1)
+    }
+
+    #[ensures(ret.len() == old(v.len()) + 1)]
+    fn push(mut v: Vec<u32>, x: u32) -> Vec<u32> {
+        v.push(x);
+        v
+    }
+
+    let a: u32 = verifier::AbstractValue::abstract_value();
+    let b: bool = verifier::AbstractValue::abstract_value();
+    clamp(a);
+    succ(if b { Some(a) } else { None });
+    push(vec![a], a);
+}
+
+/// Test of function contracts (the postcondition does not hold)
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn contracts_should_fail() {
+    #[cfg(not(feature = "verifier-crux"))]
+    verifier::expect(Some("postcondition failed"));
+
+    #[requires(a <= 100 && b <= 100)]
+    #[ensures(ret > a)]
+    fn add(a: u32, b: u32) -> u32 {
+        a + b
+    }
+
+    let a: u32 = verifier::AbstractValue::abstract_value();
+    let b: u32 = verifier::AbstractValue::abstract_value();
+    add(a, b);
+}
+
+/// Test of loop_bound! and invariant!
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn loops() {
+    let n: usize = verifier::AbstractValue::abstract_value();
+    verifier::assume(n <= 8);
+
+    let mut sum = 0;
+    verifier::loop_bound!(
+        8,
+        for i in 0..n {
+            verifier::invariant!(sum == i);
+            sum += 1;
+        }
+    );
+    assert!(sum == n);
+}
+
+/// Test of loop_bound! (the loop exceeds its bound)
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn loop_bound_should_fail() {
+    #[cfg(not(feature = "verifier-crux"))]
+    verifier::expect(Some("loop bound 8 exceeded"));
+
+    let n: usize = verifier::AbstractValue::abstract_value();
+    verifier::assume(n <= 9);
+
+    let mut i = 0;
+    verifier::loop_bound!(
+        8,
+        while i < n {
+            i += 1;
+        }
+    );
+}
+
+/// Test of forall and exists
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn quantifiers() {
+    let v = verifier::verifier_nondet_bytes(8);
+    verifier::assume(verifier::forall(0..8, |i| v[i] < 10));
+    verifier::assume(verifier::exists(v.iter(), |x| *x == 5));
+
+    assert!(v.iter().all(|x| *x < 10));
+    assert!(v.contains(&5));
+}
+
+/// Test of derive(AbstractValue)
+#[cfg_attr(not(feature = "verifier-crux"), test)]
+#[cfg_attr(feature = "verifier-crux", crux_test)]
+fn derive_abstract_value() {
+    #[derive(AbstractValue)]
+    struct Point {
+        #[abstract_where(|x| *x < 10)]
+        x: u32,
+        y: u32,
+    }
+
+    #[derive(AbstractValue)]
+    enum Shape {
+        Empty,
+        Circle(Point, #[abstract_where(|r| *r > 0)] u32),
+        Rectangle {
+            corner: Point,
+            width: u8,
+            height: u8,
+        },
+    }
+
+    match Shape::abstract_value() {
+        Shape::Empty => (),
+        Shape::Circle(centre, radius) => {
+            assert!(centre.x < 10);
+            assert!(radius > 0);
+            let _ = centre.y;
+        }
+        Shape::Rectangle {
+            corner,
+            width,
+            height,
+        } => {
+            assert!(corner.x < 10);
+            assert!(width as u32 * height as u32 <= 255 * 255);
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////
 // End
 ////////////////////////////////////////////////////////////////
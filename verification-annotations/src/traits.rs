@@ -15,6 +15,9 @@
 
 use crate::verifier::assume;
 
+// `#[derive(AbstractValue)]` for structs and enums
+pub use verification_macros::AbstractValue;
+
 /// Create a non-deterministic value with the same type as the argument
 ///
 /// The argument does not influence the result of the function.
@@ -71,6 +74,44 @@ pub trait Symbolic: Sized {
     }
 }
 
+/// Call a function with abstract values for all of its arguments
+///
+/// This is implemented for functions (and closures) of up to 8 arguments
+/// whose argument types implement `AbstractValue`.
+/// (`Args` is the tuple of argument types.)
+pub trait AbstractCall<Args> {
+    type Output;
+
+    fn call_abstract(self) -> Self::Output;
+}
+
+macro_rules! impl_abstract_call {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg: AbstractValue),*> AbstractCall<($($arg,)*)> for F
+        where
+            F: FnOnce($($arg),*) -> R,
+        {
+            type Output = R;
+
+            #[allow(non_snake_case)]
+            fn call_abstract(self) -> R {
+                $(let $arg = $arg::abstract_value();)*
+                self($($arg),*)
+            }
+        }
+    };
+}
+
+impl_abstract_call!();
+impl_abstract_call!(A0);
+impl_abstract_call!(A0, A1);
+impl_abstract_call!(A0, A1, A2);
+impl_abstract_call!(A0, A1, A2, A3);
+impl_abstract_call!(A0, A1, A2, A3, A4);
+impl_abstract_call!(A0, A1, A2, A3, A4, A5);
+impl_abstract_call!(A0, A1, A2, A3, A4, A5, A6);
+impl_abstract_call!(A0, A1, A2, A3, A4, A5, A6, A7);
+
 /////////////////////////////////////////////////////////////////
 // End
 /////////////////////////////////////////////////////////////////
@@ -85,6 +85,26 @@ pub fn expect(_msg: Option<&str>) {
     panic!("not implemented")
 }
 
+/// Check that a loop has not been entered more than `bound` times
+/// (see `loop_bound!`)
+pub fn check_loop_bound(iterations: usize, bound: usize) {
+    crucible::crucible_assert!(
+        iterations <= bound,
+        "VERIFIER: loop bound {} exceeded",
+        bound
+    );
+}
+
+/// Record that the current path reached an assertion
+///
+/// This is only tracked by KLEE (and is ignored).
+pub fn assertion_reached() {}
+
+/// Record whether condition `cond` is true on some path (see `cover!`)
+///
+/// Cover points are not supported by Crux (and are ignored).
+pub fn cover(_cond: bool, _label: &str) {}
+
 #[macro_export]
 macro_rules! assert {
     ($cond:expr) => {
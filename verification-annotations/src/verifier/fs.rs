@@ -0,0 +1,77 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/////////////////////////////////////////////////////////////////
+// Virtual files
+//
+// The file system model is part of the runtime library
+// (runtime/src/fs.c): during verification, once a virtual file has been
+// added, `std::fs::File::open` can only open the virtual files that were
+// added with these functions.
+// When replaying tests, the virtual files hide real files with the same path
+// (and other paths refer to real files).
+// Standard input can also be replaced with virtual (e.g., symbolic) contents.
+/////////////////////////////////////////////////////////////////
+
+use std::ffi::CString;
+use std::os::raw;
+
+use super::{assume, verifier_nondet_bytes, with_name, AbstractValue};
+
+extern "C" {
+    fn __rvt_fs_add_file(path: *const raw::c_char, data: *const u8, size: usize);
+    fn __rvt_fs_set_stdin(data: *const u8, size: usize);
+}
+
+fn add(path: &str, contents: Vec<u8>, size: usize) {
+    let path = CString::new(path).expect("path must not contain nul characters");
+    // The file system refers to the path and the contents from now on.
+    let path = Box::leak(path.into_boxed_c_str());
+    let contents = Box::leak(contents.into_boxed_slice());
+    unsafe { __rvt_fs_add_file(path.as_ptr(), contents.as_ptr(), size) }
+}
+
+/// Add a virtual file `path` with contents `contents`
+///
+/// The contents can be concrete or symbolic (e.g., created with
+/// `verifier_nondet_bytes`).
+/// Adding a file that already exists replaces its contents.
+pub fn add_file(path: &str, contents: Vec<u8>) {
+    let size = contents.len();
+    add(path, contents, size)
+}
+
+/// Add a virtual file `path` whose size (up to `max_size` bytes) and
+/// contents are symbolic
+///
+/// The symbolic values are named after the path.
+pub fn add_symbolic_file(path: &'static str, max_size: usize) {
+    let (size, contents) = with_name(path, || {
+        let size = usize::abstract_value();
+        assume(size <= max_size);
+        (size, verifier_nondet_bytes(max_size))
+    });
+    add(path, contents, size)
+}
+
+/// Replace the contents of standard input with `contents`
+///
+/// The contents can be concrete or symbolic.
+pub fn set_stdin(contents: Vec<u8>) {
+    let size = contents.len();
+    // The file system refers to the contents from now on.
+    let contents = Box::leak(contents.into_boxed_slice());
+    unsafe { __rvt_fs_set_stdin(contents.as_ptr(), size) }
+}
+
+/// Replace the contents of standard input with `n` symbolic bytes
+///
+/// The symbolic bytes are named "stdin".
+pub fn set_symbolic_stdin(n: usize) {
+    set_stdin(with_name("stdin", || verifier_nondet_bytes(n)))
+}
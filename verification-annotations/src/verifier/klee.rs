@@ -74,6 +74,13 @@ unsafe fn get_value_usize(x: usize) -> usize {
     klee_get_value_i64(x as i64) as usize
 }
 
+/// Make `length` bytes at `data` symbolic, naming them after the current
+/// name (see `with_name`) and the type `ty`.
+unsafe fn make_symbolic(data: *mut raw::c_void, length: usize, ty: &str) {
+    let name = super::symbolic_name(ty);
+    klee_make_symbolic(data, length, name.as_ptr())
+}
+
 /// Create instance for any type consisting of contiguous memory
 /// where all bit-patterns are legal values of the type.
 macro_rules! make_verifier_nondet {
@@ -84,8 +91,7 @@ macro_rules! make_verifier_nondet {
                 unsafe {
                     let data = std::mem::transmute(&mut r);
                     let length = std::mem::size_of::<$typ>();
-                    let null = 0 as *const i8;
-                    klee_make_symbolic(data, length, null)
+                    make_symbolic(data, length, stringify!($typ))
                 }
                 return r;
             }
@@ -120,7 +126,8 @@ make_verifier_nondet!(f64, klee_get_value_d);
 
 impl VerifierNonDet for bool {
     fn verifier_nondet(self) -> Self {
-        let c = VerifierNonDet::verifier_nondet(0u8);
+        let mut c = 0u8;
+        unsafe { make_symbolic(&mut c as *mut u8 as *mut raw::c_void, 1, "bool") }
         assume(c == 0 || c == 1);
         c == 1
     }
@@ -134,6 +141,17 @@ impl VerifierNonDet for bool {
     }
 }
 
+/// Allocate a symbolic vector of bytes that is a single symbolic object
+/// of type `ty`.
+pub(super) fn nondet_bytes_named(n: usize, ty: &str) -> Vec<u8> {
+    let mut v = vec![0u8; n];
+    // Empty vectors do not have an allocation to make symbolic.
+    if n > 0 {
+        unsafe { make_symbolic(v.as_mut_ptr() as *mut raw::c_void, n, ty) }
+    }
+    v
+}
+
 /// Assume that condition `cond` is true
 ///
 /// Any paths found must satisfy this assumption.
@@ -271,6 +289,39 @@ pub fn expect(msg: Option<&str>) {
     }
 }
 
+/// Check that a loop has not been entered more than `bound` times
+/// (see `loop_bound!`)
+pub fn check_loop_bound(iterations: usize, bound: usize) {
+    crate::verifier::assert!(iterations <= bound, "loop bound {} exceeded", bound)
+}
+
+/// Record that the current path reached an assertion
+///
+/// This is reported once per path so that cargo-verify can detect tests
+/// that reach no assertion (see its `--require-assertions` option).
+pub fn assertion_reached() {
+    thread_local! {
+        static REACHED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    }
+    if !REACHED.with(|r| r.replace(true)) {
+        eprintln!("VERIFIER_ASSERT: reached");
+    }
+}
+
+/// Record whether condition `cond` is true on some path (see `cover!`)
+///
+/// Branching on `cond` would split the rest of the test into two paths
+/// so the case where `cond` is true is explored on a separate path that
+/// ends here.
+pub fn cover(cond: bool, label: &str) {
+    eprintln!("VERIFIER_COVER: reached {}", label);
+    if super::with_name("cover", || VerifierNonDet::verifier_nondet(false)) {
+        assume(cond);
+        eprintln!("VERIFIER_COVER: hit {}", label);
+        reject()
+    }
+}
+
 /////////////////////////////////////////////////////////////////
 // End
 /////////////////////////////////////////////////////////////////
@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::cell::Cell;
 use std::default::Default;
 use std::ffi::CString;
 
@@ -13,6 +14,8 @@ use std::ffi::CString;
 #[cfg(feature = "verifier-klee")]
 mod klee;
 #[cfg(feature = "verifier-klee")]
+use klee::nondet_bytes_named;
+#[cfg(feature = "verifier-klee")]
 pub use klee::*;
 
 #[cfg(feature = "verifier-crux")]
@@ -27,16 +30,66 @@ mod seahorn;
 #[cfg(feature = "verifier-seahorn")]
 pub use seahorn::*;
 
+// Virtual files (using the file system model in the runtime library)
+#[cfg(any(feature = "verifier-klee", feature = "verifier-seahorn"))]
+pub mod fs;
+#[cfg(any(feature = "verifier-klee", feature = "verifier-seahorn"))]
+pub use fs::set_symbolic_stdin;
+
+thread_local! {
+    // The name given to symbolic values (see `with_name`).
+    static NAME: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+/// Use `name` as the name of the symbolic values created by `f`
+///
+/// The name is used (together with the type of the value) by backends that
+/// support naming symbolic values in counterexamples (e.g., KLEE's .ktest
+/// files).
+/// This is used by `proptest!` to name values after the parameters of tests.
+pub fn with_name<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    let old = NAME.with(|n| n.replace(Some(name)));
+    let r = f();
+    NAME.with(|n| n.set(old));
+    r
+}
+
+/// The name of a symbolic value of type `ty`: "name: ty" or just "ty" if
+/// there is no name.
+#[cfg(feature = "verifier-klee")]
+fn symbolic_name(ty: &str) -> CString {
+    let name = match NAME.with(Cell::get) {
+        Some(name) => format!("{}: {}", name, ty),
+        None => ty.to_string(),
+    };
+    // Names cannot contain nul characters.
+    CString::new(name.replace('\0', "")).unwrap()
+}
+
 /// Allocate a symbolic vector of bytes
+#[cfg(not(feature = "verifier-klee"))]
 pub fn verifier_nondet_bytes(n: usize) -> Vec<u8> {
     let mut v: Vec<u8> = Vec::with_capacity(n);
     v.resize_with(n, || VerifierNonDet::verifier_nondet(0u8));
     return v;
 }
 
+/// Allocate a symbolic vector of bytes
+///
+/// (With KLEE, the vector is a single symbolic object.)
+#[cfg(feature = "verifier-klee")]
+pub fn verifier_nondet_bytes(n: usize) -> Vec<u8> {
+    nondet_bytes_named(n, "Vec<u8>")
+}
+
+#[cfg(not(feature = "verifier-klee"))]
+fn nondet_bytes_named(n: usize, _ty: &str) -> Vec<u8> {
+    verifier_nondet_bytes(n)
+}
+
 /// Allocate a symbolic CString
 pub fn verifier_nondet_cstring(size_excluding_null: usize) -> CString {
-    let mut r = verifier_nondet_bytes(size_excluding_null + 1);
+    let mut r = nondet_bytes_named(size_excluding_null + 1, "CString");
     for i in 0..size_excluding_null {
         assume(r[i] != 0u8);
     }
@@ -47,7 +100,7 @@ pub fn verifier_nondet_cstring(size_excluding_null: usize) -> CString {
 /// Allocate a symbolic ASCII String
 /// (ASCII strings avoid the complexity of UTF-8)
 pub fn verifier_nondet_ascii_string(n: usize) -> String {
-    let r = verifier_nondet_bytes(n);
+    let r = nondet_bytes_named(n, "String");
     for i in 0..n {
         assume(r[i] != 0u8);
         assume(r[i].is_ascii());
@@ -65,11 +118,40 @@ impl<T: VerifierNonDet + Default> AbstractValue for T {
 }
 
 impl<T: VerifierNonDet + Default> Symbolic for T {
-    fn symbolic(_desc: &'static str) -> Self {
-        Self::verifier_nondet(Self::default())
+    fn symbolic(desc: &'static str) -> Self {
+        with_name(desc, || Self::verifier_nondet(Self::default()))
     }
 }
 
+/// Call `f` with abstract values for all of its arguments
+///
+/// This is used by `cargo verify --entry` to verify library functions.
+pub fn call_abstract<Args, F: crate::traits::AbstractCall<Args>>(f: F) -> F::Output {
+    f.call_abstract()
+}
+
+// Quantifiers
+//
+// `forall` and `exists` are convenience wrappers that evaluate the condition
+// for each element of a finite range: they are not quantifiers of the
+// backends (lowering them to SeaHorn quantifiers or KLEE array constraints
+// is not implemented).
+// The conditions are combined without short-circuiting (`&` and `|` instead
+// of `&&` and `||`) so that evaluating them does not fork execution (with
+// KLEE) or add branches (with SeaHorn).
+// For the same reason, conditions that combine several tests should use `&`
+// and `|` (e.g., `|i| (v[i] != 0) & v[i].is_ascii()`).
+
+/// Is `f(i)` true for every `i` in `range`?
+pub fn forall<I: IntoIterator>(range: I, f: impl FnMut(I::Item) -> bool) -> bool {
+    range.into_iter().map(f).fold(true, |acc, b| acc & b)
+}
+
+/// Is `f(i)` true for some `i` in `range`?
+pub fn exists<I: IntoIterator>(range: I, f: impl FnMut(I::Item) -> bool) -> bool {
+    range.into_iter().map(f).fold(false, |acc, b| acc | b)
+}
+
 // Macros
 
 #[macro_export]
@@ -77,6 +159,7 @@ macro_rules! assert {
     ($cond:expr,) => { $crate::verifier::assert!($cond) };
     ($cond:expr) => { $crate::verifier::assert!($cond, "assertion failed: {}", stringify!($cond)) };
     ($cond:expr, $($arg:tt)+) => {{
+        $crate::verifier::assertion_reached();
         if ! $cond {
             let message = format!($($arg)+);
             eprintln!("VERIFIER: panicked at '{}', {}:{}:{}",
@@ -142,10 +225,47 @@ macro_rules! unreachable {
     };
 }
 
+/// Check that condition `cond` is true on some path
+///
+/// This is the positive counterpart of `unreachable!`: it does not
+/// report an error but cargo-verify lists the cover points of each test
+/// and whether their condition was true on some path (or whether it is
+/// unreachable).
+/// This is useful to check that a harness exercises interesting cases.
+///
+/// Cover points are only supported by KLEE: with SeaHorn and Crux,
+/// `cover!` does nothing.
+#[macro_export]
+macro_rules! cover {
+    ($cond:expr) => {
+        $crate::verifier::cover($cond, stringify!($cond))
+    };
+    ($cond:expr, $label:expr) => {
+        $crate::verifier::cover($cond, $label)
+    };
+}
+
+/// Loop invariant: check that `cond` holds at this point of each iteration
+///
+/// An invariant is usually placed at the start of the loop body.
+/// It is only checked as an assertion (on every backend): it is not given
+/// to model checkers such as SeaHorn as an inductive invariant of the loop.
+#[macro_export]
+macro_rules! invariant {
+    ($cond:expr) => {
+        $crate::verifier::assert!($cond)
+    };
+}
+
 pub use crate::assert;
 pub use crate::assert_eq;
 pub use crate::assert_ne;
+pub use crate::cover;
+pub use crate::invariant;
 pub use crate::unreachable;
 
+// Loop bounds: `loop_bound!(n, loop)` (see `check_loop_bound`)
+pub use verification_macros::loop_bound;
+
 #[cfg(feature = "verifier-klee")]
 pub use crate::coherent;
@@ -54,14 +54,20 @@ pub fn reject() -> ! {
     panic!("Unreachable, should have been rejected!");
 }
 
-/// Detect whether the program is being run symbolically in KLEE
-/// or being replayed using the kleeRuntest runtime.
+// Set to 1 (by overriding this definition) when a SeaHorn counterexample is
+// replayed.
+// This is a mutable static (and read with a volatile load) so that the
+// compiler cannot assume that it is always 0.
+#[no_mangle]
+static mut __VERIFIER_is_replay: u8 = 0;
+
+/// Detect whether the program is being run symbolically in SeaHorn
+/// or a counterexample is being replayed.
 ///
 /// This is used to decide whether to display the values of
 /// variables that may be either symbolic or concrete.
 pub fn is_replay() -> bool {
-    // panic!("SeaHorn doesn't support replay.")
-    false
+    unsafe { std::ptr::read_volatile(std::ptr::addr_of!(__VERIFIER_is_replay)) != 0 }
 }
 
 /// Reject the current execution with a verification failure
@@ -87,6 +93,26 @@ pub fn expect(msg: Option<&str>) {
     }
 }
 
+/// Check that a loop has not been entered more than `bound` times
+/// (see `loop_bound!`)
+///
+/// cargo-verify also unrolls loops one more time than the largest literal
+/// bound of the `loop_bound!` annotations of the crate (SeaHorn's `--bound`)
+/// so that exceeding a bound is detected.
+pub fn check_loop_bound(iterations: usize, bound: usize) {
+    crate::verifier::assert!(iterations <= bound, "loop bound {} exceeded", bound)
+}
+
+/// Record that the current path reached an assertion
+///
+/// This is only tracked by KLEE (and is ignored).
+pub fn assertion_reached() {}
+
+/// Record whether condition `cond` is true on some path (see `cover!`)
+///
+/// Cover points are not supported by SeaHorn (and are ignored).
+pub fn cover(_cond: bool, _label: &str) {}
+
 macro_rules! make_nondet {
     ($typ:ty, $ext:ident, $v:expr) => {
         extern "C" {
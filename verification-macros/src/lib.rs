@@ -0,0 +1,706 @@
+// Copyright 2021 The Propverify authors
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/////////////////////////////////////////////////////////////////
+// Function contracts
+//
+// `#[requires(cond)]` and `#[ensures(cond)]` attach a precondition and a
+// postcondition to a function.
+// When verifying (`cfg(verify)`), preconditions are assumed and
+// postconditions are checked with `verifier::assert!`.
+// Otherwise, both are checked with `debug_assert!`.
+// The body of a function with a postcondition is evaluated in a loop that
+// `return` and `?` break out of so that the postcondition is checked on
+// every exit from the function.
+//
+// Loop bounds
+//
+// `loop_bound!(n, loop)` counts the iterations of a loop and passes the
+// count to the verifier (see `verifier::check_loop_bound`).
+//
+// Abstract values
+//
+// `#[derive(AbstractValue)]` implements `AbstractValue` for structs (by
+// creating an abstract value for each field) and enums (by also creating an
+// abstract discriminant).
+//
+// Arbitrary values (for propverify)
+//
+// `#[derive(Arbitrary)]` implements propverify's `Arbitrary` trait like
+// proptest-derive (including `#[proptest(...)]` attributes) so that
+// proptest tests that use it work with propverify.
+/////////////////////////////////////////////////////////////////
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote,
+    visit_mut::{self, VisitMut},
+    Attribute, Block, Data, DeriveInput, Expr, Fields, FnArg, GenericArgument, GenericParam, Item,
+    ItemFn, Lifetime, Lit, Macro, Meta, NestedMeta, Pat, PatType, PathArguments, ReturnType, Token,
+    Type,
+};
+
+/// Precondition of a function
+///
+/// The condition can refer to the arguments of the function.
+///
+/// ```ignore
+/// #[requires(b != 0)]
+/// fn div(a: u32, b: u32) -> u32 {
+///     a / b
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn requires(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let cond = parse_macro_input!(attr as Expr);
+    let mut f = parse_macro_input!(item as ItemFn);
+
+    let text = cond.to_token_stream().to_string();
+    let body = &f.block;
+    f.block = parse_quote!({
+        #[cfg(verify)]
+        ::verification_annotations::verifier::assume(#cond);
+        #[cfg(not(verify))]
+        debug_assert!(#cond, "precondition failed: {}", #text);
+        #body
+    });
+
+    f.into_token_stream().into()
+}
+
+/// Postcondition of a function
+///
+/// The condition can refer to the arguments of the function, to the
+/// result of the function (`ret`) and to the value of an expression on entry
+/// to the function (`old(expr)`).
+/// Arguments that the function moves can only be referred to with `old`.
+///
+/// ```ignore
+/// #[ensures(ret <= a)]
+/// fn div(a: u32, b: u32) -> u32 {
+///     a / b
+/// }
+///
+/// #[ensures(ret.len() == old(v.len()) + 1)]
+/// fn push(mut v: Vec<u32>, x: u32) -> Vec<u32> {
+///     v.push(x);
+///     v
+/// }
+/// ```
+///
+/// The postcondition is also checked when the function returns early with
+/// `return` or `?` (in functions that return `Result` or `Option`).
+#[proc_macro_attribute]
+pub fn ensures(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut cond = parse_macro_input!(attr as Expr);
+    let mut f = parse_macro_input!(item as ItemFn);
+
+    if let Some(asyncness) = f.sig.asyncness {
+        return syn::Error::new_spanned(asyncness, "#[ensures] does not support async functions")
+            .to_compile_error()
+            .into();
+    }
+    if let Some(constness) = f.sig.constness {
+        return syn::Error::new_spanned(constness, "#[ensures] does not support const functions")
+            .to_compile_error()
+            .into();
+    }
+    for input in &f.sig.inputs {
+        if let FnArg::Typed(PatType { pat, .. }) = input {
+            if let Pat::Ident(p) = &**pat {
+                if p.ident == "ret" {
+                    return syn::Error::new_spanned(
+                        &p.ident,
+                        "#[ensures] refers to the result as `ret` so no argument can be named `ret`",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            }
+        }
+    }
+
+    // Replace each `old(expr)` in the condition with a variable that is
+    // initialized on entry to the function.
+    let text = cond.to_token_stream().to_string();
+    let mut olds = OldValues { exprs: vec![] };
+    olds.visit_expr_mut(&mut cond);
+    let old_exprs = &olds.exprs;
+    let old_names: Vec<_> = (0..old_exprs.len()).map(old_name).collect();
+
+    // The body is evaluated in a loop and `return` and `?` in the body break
+    // out of the loop so that they do not skip the postcondition.
+    let label = Lifetime::new("'body", Span::mixed_site());
+    let mut exits = Exits {
+        label: &label,
+        try_kind: try_kind(&f.sig.output),
+        error: None,
+    };
+    exits.visit_block_mut(&mut f.block);
+    if let Some(err) = exits.error {
+        return err.to_compile_error().into();
+    }
+
+    // The type of the result is inferred if it contains `impl Trait` (which
+    // is not allowed in the type of a variable).
+    let ty = match &f.sig.output {
+        ReturnType::Default => quote!(: ()),
+        ReturnType::Type(_, ty) if contains_impl_trait(ty) => quote!(),
+        ReturnType::Type(_, ty) => quote!(: #ty),
+    };
+    let result = Ident::new("result", Span::mixed_site());
+    let body = &f.block;
+    f.block = parse_quote!({
+        #(let #old_names = #old_exprs;)*
+        #[allow(unreachable_code, unused_braces, clippy::diverging_sub_expression, clippy::never_loop)]
+        let #result #ty = #label: loop {
+            break #label #body;
+        };
+        let ret = #result;
+        #[cfg(verify)]
+        ::verification_annotations::verifier::assert!(#cond, "postcondition failed: {}", #text);
+        #[cfg(not(verify))]
+        debug_assert!(#cond, "postcondition failed: {}", #text);
+        ret
+    });
+
+    f.into_token_stream().into()
+}
+
+/// The variable that holds the value of the `i`th `old(expr)` of a
+/// postcondition.
+fn old_name(i: usize) -> Ident {
+    Ident::new(&format!("old_{}", i), Span::mixed_site())
+}
+
+/// Replace each `old(expr)` with a variable (see `old_name`) and collect
+/// the expressions.
+struct OldValues {
+    exprs: Vec<Expr>,
+}
+
+impl VisitMut for OldValues {
+    fn visit_expr_mut(&mut self, e: &mut Expr) {
+        if let Expr::Call(call) = e {
+            if let Expr::Path(func) = &*call.func {
+                if func.path.is_ident("old") && call.args.len() == 1 {
+                    let name = old_name(self.exprs.len());
+                    self.exprs.push(call.args[0].clone());
+                    *e = parse_quote!(#name);
+                    return;
+                }
+            }
+        }
+        visit_mut::visit_expr_mut(self, e)
+    }
+}
+
+/// The kind of value that `?` returns early with.
+#[derive(Clone, Copy)]
+enum TryKind {
+    Result,
+    Option,
+}
+
+/// The kind of value that `?` returns early with in a function with return
+/// type `output` (if it is a `Result` or an `Option`).
+fn try_kind(output: &ReturnType) -> Option<TryKind> {
+    match output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(path) => match path.path.segments.last() {
+                Some(segment) if segment.ident == "Result" => Some(TryKind::Result),
+                Some(segment) if segment.ident == "Option" => Some(TryKind::Option),
+                _ => None,
+            },
+            _ => None,
+        },
+        ReturnType::Default => None,
+    }
+}
+
+/// Replace `return` and `?` in the body of a function (but not in closures,
+/// async blocks or nested items) with a `break` out of the loop `label`.
+struct Exits<'a> {
+    label: &'a Lifetime,
+    try_kind: Option<TryKind>,
+    error: Option<syn::Error>,
+}
+
+impl<'a> Exits<'a> {
+    fn report(&mut self, err: syn::Error) {
+        match &mut self.error {
+            Some(error) => error.combine(err),
+            None => self.error = Some(err),
+        }
+    }
+}
+
+impl<'a> VisitMut for Exits<'a> {
+    fn visit_expr_mut(&mut self, e: &mut Expr) {
+        let label = self.label;
+        match e {
+            Expr::Closure(_) | Expr::Async(_) | Expr::TryBlock(_) => (),
+            Expr::Return(r) => {
+                visit_mut::visit_expr_return_mut(self, r);
+                *e = match &r.expr {
+                    Some(value) => parse_quote!(break #label #value),
+                    None => parse_quote!(break #label),
+                };
+            }
+            Expr::Try(t) => {
+                visit_mut::visit_expr_try_mut(self, t);
+                let value = &t.expr;
+                let v = Ident::new("value", Span::mixed_site());
+                *e = match self.try_kind {
+                    Some(TryKind::Result) => parse_quote!(
+                        match #value {
+                            ::core::result::Result::Ok(#v) => #v,
+                            ::core::result::Result::Err(#v) => {
+                                break #label ::core::result::Result::Err(::core::convert::From::from(#v))
+                            }
+                        }
+                    ),
+                    Some(TryKind::Option) => parse_quote!(
+                        match #value {
+                            ::core::option::Option::Some(#v) => #v,
+                            ::core::option::Option::None => break #label ::core::option::Option::None,
+                        }
+                    ),
+                    None => {
+                        self.report(syn::Error::new_spanned(
+                            t.question_token,
+                            "#[ensures] only supports `?` in functions that return `Result` or `Option`",
+                        ));
+                        return;
+                    }
+                };
+            }
+            Expr::Macro(m) => self.visit_macro_mut(&mut m.mac),
+            _ => visit_mut::visit_expr_mut(self, e),
+        }
+    }
+
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        if let Item::Macro(m) = item {
+            self.visit_macro_mut(&mut m.mac)
+        }
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut Macro) {
+        if has_exit(mac.tokens.clone()) {
+            self.report(syn::Error::new_spanned(
+                mac,
+                "#[ensures] does not support `return` or `?` in macro calls",
+            ))
+        }
+    }
+}
+
+/// Whether `tokens` contain `return` or `?`.
+fn has_exit(tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "return",
+        TokenTree::Punct(punct) => punct.as_char() == '?',
+        TokenTree::Group(group) => has_exit(group.stream()),
+        TokenTree::Literal(_) => false,
+    })
+}
+
+/// Whether type `ty` contains `impl Trait`.
+fn contains_impl_trait(ty: &Type) -> bool {
+    match ty {
+        Type::ImplTrait(_) => true,
+        Type::Array(t) => contains_impl_trait(&t.elem),
+        Type::Group(t) => contains_impl_trait(&t.elem),
+        Type::Paren(t) => contains_impl_trait(&t.elem),
+        Type::Ptr(t) => contains_impl_trait(&t.elem),
+        Type::Reference(t) => contains_impl_trait(&t.elem),
+        Type::Slice(t) => contains_impl_trait(&t.elem),
+        Type::Tuple(t) => t.elems.iter().any(contains_impl_trait),
+        Type::Path(t) => t
+            .path
+            .segments
+            .iter()
+            .any(|segment| match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                    GenericArgument::Type(ty) => contains_impl_trait(ty),
+                    _ => false,
+                }),
+                PathArguments::Parenthesized(args) => {
+                    args.inputs.iter().any(contains_impl_trait)
+                        || match &args.output {
+                            ReturnType::Type(_, ty) => contains_impl_trait(ty),
+                            ReturnType::Default => false,
+                        }
+                }
+                PathArguments::None => false,
+            }),
+        _ => false,
+    }
+}
+
+/// The arguments of `loop_bound!`: the bound and the loop.
+struct LoopBound {
+    bound: Expr,
+    the_loop: Expr,
+}
+
+impl Parse for LoopBound {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let bound = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let the_loop = input.parse()?;
+        Ok(LoopBound { bound, the_loop })
+    }
+}
+
+/// Bound the number of iterations of a loop
+///
+/// The loop (a `for`, `while` or `loop` expression) is the second argument.
+/// The verifier is told how many times the loop body has been entered at the
+/// start of each iteration (see `verifier::check_loop_bound`).
+///
+/// ```ignore
+/// verifier::loop_bound!(10, for x in v.iter() {
+///     sum += x;
+/// });
+/// ```
+#[proc_macro]
+pub fn loop_bound(input: TokenStream) -> TokenStream {
+    let LoopBound {
+        bound,
+        mut the_loop,
+    } = parse_macro_input!(input as LoopBound);
+
+    // The counter is not visible to the loop body.
+    let count = syn::Ident::new("iterations", Span::mixed_site());
+    let limit = syn::Ident::new("bound", Span::mixed_site());
+    let body = match &mut the_loop {
+        Expr::ForLoop(l) => &mut l.body,
+        Expr::While(l) => &mut l.body,
+        Expr::Loop(l) => &mut l.body,
+        _ => {
+            return syn::Error::new_spanned(
+                the_loop,
+                "expected a 'for', 'while' or 'loop' expression",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+    let old_body: &Block = body;
+    *body = parse_quote!({
+        #count += 1;
+        ::verification_annotations::verifier::check_loop_bound(#count, #limit);
+        #old_body
+    });
+
+    quote!({
+        let #limit: usize = #bound;
+        let mut #count: usize = 0;
+        #the_loop
+    })
+    .into()
+}
+
+/// Implement `AbstractValue` for a struct or enum
+///
+/// Each field is created with `AbstractValue::abstract_value` (so the type
+/// of each field must implement `AbstractValue`) or, if the field has an
+/// `#[abstract_where(predicate)]` attribute, with
+/// `AbstractValue::abstract_where(predicate)`.
+/// For enums, the variant is chosen by an abstract discriminant.
+///
+/// ```ignore
+/// #[derive(AbstractValue)]
+/// enum Shape {
+///     Circle { #[abstract_where(|r| *r > 0)] radius: u32 },
+///     Rectangle(u32, u32),
+/// }
+/// ```
+#[proc_macro_derive(AbstractValue, attributes(abstract_where))]
+pub fn derive_abstract_value(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+
+    let body = match &input.data {
+        Data::Struct(data) => match abstract_fields(&data.fields) {
+            Ok(fields) => quote!(Self #fields),
+            Err(err) => return err.to_compile_error().into(),
+        },
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                return syn::Error::new_spanned(
+                    &input.ident,
+                    "#[derive(AbstractValue)] does not support empty enums",
+                )
+                .to_compile_error()
+                .into();
+            }
+            let last = data.variants.len() - 1;
+            let mut arms = vec![];
+            for (i, variant) in data.variants.iter().enumerate() {
+                let name = &variant.ident;
+                let fields = match abstract_fields(&variant.fields) {
+                    Ok(fields) => fields,
+                    Err(err) => return err.to_compile_error().into(),
+                };
+                // The last variant takes all remaining discriminants.
+                let pattern = if i == last { quote!(_) } else { quote!(#i) };
+                arms.push(quote!(#pattern => Self::#name #fields,));
+            }
+            quote!(
+                match <usize as ::verification_annotations::traits::AbstractValue>::abstract_value() {
+                    #(#arms)*
+                }
+            )
+        }
+        Data::Union(_) => {
+            return syn::Error::new_spanned(
+                &input.ident,
+                "#[derive(AbstractValue)] does not support unions",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+
+    // Type parameters must implement AbstractValue.
+    for param in input.generics.params.iter_mut() {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(
+                ::verification_annotations::traits::AbstractValue
+            ));
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote!(
+        impl #impl_generics ::verification_annotations::traits::AbstractValue for #name #ty_generics #where_clause {
+            fn abstract_value() -> Self {
+                #body
+            }
+        }
+    )
+    .into()
+}
+
+/// The field initializers (including braces or parentheses) that create an
+/// abstract value for each of `fields`.
+fn abstract_fields(fields: &Fields) -> syn::Result<TokenStream2> {
+    let mut values = vec![];
+    for field in fields {
+        let ty = &field.ty;
+        let predicate = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path.is_ident("abstract_where"))
+            .map(|attr| attr.parse_args::<Expr>())
+            .transpose()?;
+        let value = match predicate {
+            Some(predicate) => quote!(
+                <#ty as ::verification_annotations::traits::AbstractValue>::abstract_where(#predicate)
+            ),
+            None => {
+                quote!(<#ty as ::verification_annotations::traits::AbstractValue>::abstract_value())
+            }
+        };
+        values.push(match &field.ident {
+            Some(name) => quote!(#name: #value),
+            None => value,
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => quote!({ #(#values),* }),
+        Fields::Unnamed(_) => quote!(( #(#values),* )),
+        Fields::Unit => quote!(),
+    })
+}
+
+/// Implement propverify's `Arbitrary` trait for a struct or enum
+///
+/// This is compatible with proptest-derive: by default, each field is
+/// generated by `any::<T>()` (so the type of each field must implement
+/// `Arbitrary`) and the variant of an enum is chosen like `prop_oneof!`.
+/// The following `#[proptest(...)]` attributes are supported:
+///
+/// - `#[proptest(strategy = "EXPR")]` on a field: use the strategy EXPR.
+/// - `#[proptest(value = "EXPR")]` on a field: use the value EXPR.
+/// - `#[proptest(skip)]` on a variant: never generate the variant.
+/// - `#[proptest(weight = N)]` on a variant: accepted but ignored.
+///
+/// ```ignore
+/// #[derive(Arbitrary, Debug)]
+/// struct Point {
+///     #[proptest(strategy = "0..10u32")]
+///     x: u32,
+///     y: u32,
+/// }
+/// ```
+#[proc_macro_derive(Arbitrary, attributes(proptest))]
+pub fn derive_arbitrary(input: TokenStream) -> TokenStream {
+    match arbitrary(parse_macro_input!(input as DeriveInput)) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn arbitrary(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    if let Some((name, _)) = proptest_options(&input.attrs)?.first() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("#[proptest({})] is not supported on types", name),
+        ));
+    }
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let fields = arbitrary_fields(&data.fields)?;
+            quote!(Self #fields)
+        }
+        Data::Enum(data) => {
+            let mut variants = vec![];
+            for variant in &data.variants {
+                let options = proptest_options(&variant.attrs)?;
+                if let Some((name, _)) = options
+                    .iter()
+                    .find(|(name, _)| name != "skip" && name != "weight")
+                {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        format!("#[proptest({})] is not supported on variants", name),
+                    ));
+                }
+                if options.iter().any(|(name, _)| name == "skip") {
+                    continue;
+                }
+                let name = &variant.ident;
+                let fields = arbitrary_fields(&variant.fields)?;
+                variants.push(quote!(Self::#name #fields));
+            }
+            // Choose between the variants like prop_oneof!
+            let mut variants = variants.into_iter().rev();
+            let last = variants.next().ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &input.ident,
+                    "#[derive(Arbitrary)] needs at least one variant that is not skipped",
+                )
+            })?;
+            variants.fold(last, |rest, variant| {
+                quote!(
+                    if ::propverify::prelude::verifier::AbstractValue::abstract_value() {
+                        #variant
+                    } else {
+                        #rest
+                    }
+                )
+            })
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "#[derive(Arbitrary)] does not support unions",
+            ))
+        }
+    };
+
+    // Type parameters must implement Arbitrary.
+    for param in input.generics.params.iter_mut() {
+        if let GenericParam::Type(param) = param {
+            param
+                .bounds
+                .push(parse_quote!(::propverify::prelude::Arbitrary));
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // The strategy is a function pointer (a closure that does not capture
+    // anything) so that its type can be named.
+    Ok(quote!(
+        impl #impl_generics ::propverify::prelude::Arbitrary for #name #ty_generics #where_clause {
+            type Strategy = fn() -> Self;
+            fn arbitrary() -> Self::Strategy {
+                || #body
+            }
+        }
+    ))
+}
+
+/// The field initializers (including braces or parentheses) that generate
+/// an arbitrary value for each of `fields`.
+fn arbitrary_fields(fields: &Fields) -> syn::Result<TokenStream2> {
+    let mut values = vec![];
+    for field in fields {
+        let ty = &field.ty;
+        let mut value = quote!(
+            ::propverify::prelude::Strategy::value(&::propverify::prelude::any::<#ty>())
+        );
+        for (name, lit) in proptest_options(&field.attrs)? {
+            let expr = match &lit {
+                Some(Lit::Str(s)) => s.parse::<Expr>()?,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        format!("#[proptest({})] is not supported on fields", name),
+                    ))
+                }
+            };
+            value = match name.as_str() {
+                "strategy" => quote!(::propverify::prelude::Strategy::value(&(#expr))),
+                "value" => quote!(#expr),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        format!("#[proptest({})] is not supported on fields", name),
+                    ))
+                }
+            };
+        }
+        values.push(match &field.ident {
+            Some(name) => quote!(#name: #value),
+            None => value,
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => quote!({ #(#values),* }),
+        Fields::Unnamed(_) => quote!(( #(#values),* )),
+        Fields::Unit => quote!(),
+    })
+}
+
+/// The options in the `#[proptest(...)]` attributes in `attrs`: the name of
+/// each option and its value (if it has one).
+fn proptest_options(attrs: &[Attribute]) -> syn::Result<Vec<(String, Option<Lit>)>> {
+    let mut options = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("proptest")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[proptest(...)]")),
+        };
+        for nested in list.nested {
+            let (path, lit) = match nested {
+                NestedMeta::Meta(Meta::Path(path)) => (path, None),
+                NestedMeta::Meta(Meta::NameValue(nv)) => (nv.path, Some(nv.lit)),
+                nested => {
+                    return Err(syn::Error::new_spanned(
+                        nested,
+                        "unsupported #[proptest] option",
+                    ))
+                }
+            };
+            let name = path.to_token_stream().to_string();
+            options.push((name, lit));
+        }
+    }
+    Ok(options)
+}